@@ -1,4 +1,20 @@
-use crate::utils::{ max_day, Pin};
+use chrono::prelude::*;
+
+use crate::error::Error;
+use crate::input;
+use crate::utils::{ max_day, Date, Org, Pin};
+
+
+
+/// How [full] treats a separator that doesn't match the age of the person
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// The pin is invalid, see [separator]
+    Error,
+
+    /// The separator isn't checked, for callers that want to report it some other way
+    Ignore,
+}
 
 
 
@@ -14,13 +30,10 @@ use crate::utils::{ max_day, Pin};
 /// # Returns
 /// Ture if the month is valid
 /// False if it is not
-fn check_month(month: u32) -> Result<(), &'static str> {
+fn check_month(month: u32) -> Result<(), Error> {
 
-    if month == 0 {
-        Err("Month is 0")
-
-    } else if month > 12 {
-        Err("Month over 12")
+    if month == 0 || month > 12 {
+        Err(Error::MonthOutOfRange { month })
 
     } else { // valid month
         Ok(())
@@ -36,25 +49,19 @@ fn check_month(month: u32) -> Result<(), &'static str> {
 
 
 /// Check if the day is possible given the month and year
-fn check_day(pin: Pin) -> Result<(), &'static str> {
-    let day = pin.date.day;
-    let max = max_day(pin.date.month, pin.date.year);
-
-    if day > max {
-        return if day > max+60 {
-            Err("Day invalid")
-
-        } else if day < 61 {
-            Err("Day invalid")
-
-        } else {
-            Ok(())
-        }
-    }
-
-    if day == 0 {
-        return Err("Day is 0")
-
+///
+/// The day of samordningsnummer has already had the extra 60 removed when parsing, so the
+/// same limits apply to both kinds.
+fn check_day(date: Date) -> Result<(), Error> {
+    let day = date.day;
+    let max = max_day(date.month, date.year);
+
+    if day > max || day == 0 {
+        return Err(Error::DayOutOfRange {
+            day,
+            month: date.month,
+            year: date.year,
+        })
     }
 
     // permanent
@@ -77,18 +84,11 @@ fn check_day(pin: Pin) -> Result<(), &'static str> {
 /// # Returns
 /// Ture if the date is valid 
 /// False if it is not
-fn date(pin: Pin) -> Result<(), &'static str>{
+fn date(pin: Pin) -> Result<(), Error>{
 
-    match check_month(pin.date.month) {
-        Err(reason) => return Err(reason),
-        _ => {}
-    }
+    check_month(pin.date.month)?;
 
-
-    match check_day(pin) {
-        Err(reason) => return Err(reason),
-        _ => {}
-    }
+    check_day(pin.date)?;
 
     Ok(())
 }
@@ -121,26 +121,236 @@ fn luhns(pin:[u8;10]) -> bool {
 
 
 
+/// Calculate the control char of a finnish henkilötunnus
+///
+/// The 9 digits are read as one number and the remainder when divided by 31 picks the char.
+/// Letters that are easy to mistake for digits or each other are left out.
+///
+/// # Arguments
+/// * `nums` the birth date and individual number, DDMMYYNNN.
+///
+/// # Returns
+/// The last char of the henkilötunnus
+pub fn mod31(nums: [u8;9]) -> char {
+
+    let chars = b"0123456789ABCDEFHJKLMNPRSTUVWXY";
+    let number = nums.iter().fold(0, |number, num| number * 10 + *num as u32);
+
+    char::from(chars[(number % 31) as usize])
+}
+
+
+
+
+/// Calculate the control digit for a pin
+///
+/// Uses the same weights as [luhns], the control digit is the one that makes the sum divisable
+/// by 10.
+///
+/// # Arguments
+/// * `nums` the first 9 digits of a pin.
+///
+/// # Returns
+/// The 10th digit of the pin
+pub fn control_digit(nums: [u8;9]) -> u8 {
+
+    let multiples = [0, 2, 4, 6, 8, 1, 3, 5, 7, 9];
+    let mut sum = 0;
+
+    for (i, num) in nums.iter().enumerate() {
+        sum += match i % 2 {
+            0 => multiples[*num as usize],
+            _ => *num,
+        };
+    }
+
+    (10 - sum % 10) % 10
+}
+
+
+
+
+/// Check the control digit with [luhns]
+///
+/// # Arguments
+/// * `nums` array of the digits in a pin or organisationsnummer.
+///
+/// # Returns
+/// Ok if the control digit is correct, otherwise Err with the digit it should have been.
+fn checksum(nums: [u8;10]) -> Result<(), Error> {
+
+    if luhns(nums) {
+        return Ok(())
+    }
+
+    let mut first = [0;9];
+    first.copy_from_slice(&nums[..9]);
+
+    Err(Error::Checksum {
+        expected: control_digit(first),
+        found: nums[9],
+    })
+}
+
+
+
+
+/// Check that the person was born on or before the reference date
+///
+/// Pins with only 10 digits always get a birth date in the past when parsed, but the 12 digit
+/// forms can give any year.
+///
+/// # Arguments
+/// * `date` The birth date to check, after [date] has passed
+/// * `reference` The date the pin is interpreted at
+pub fn future(date: Date, reference: NaiveDate) -> Result<(), Error> {
+    let born = (date.year, date.month, date.day);
+
+    if born > (reference.year(), reference.month(), reference.day()) {
+        return Err(Error::FutureDate {
+            day: date.day,
+            month: date.month,
+            year: date.year,
+        })
+    }
+
+    Ok(())
+}
+
+
+
+
+/// Check that a birth date exists and isn't after the reference date
+///
+/// Used for the numbers of other countries, which have their dates written the same way once
+/// the extra days or months of their special kinds are removed.
+///
+/// # Arguments
+/// * `date` The birth date with the full year
+/// * `reference` The date the number is interpreted at
+pub fn birth_date(date: Date, reference: NaiveDate) -> Result<(), Error> {
+    check_month(date.month)?;
+    check_day(date)?;
+    future(date, reference)
+}
+
+
+
+
+/// Check that a separator matches the age of the person
+///
+/// A + is used for people who are 100 or older at the reference date and a - for everyone
+/// else. The year of 10 digit pins is guessed from the separator, so this is only useful when
+/// the full year is known, like in YYYYMMDD-NNNN.
+///
+/// # Arguments
+/// * `separator` The separator that was used, - or +
+/// * `date` The birth date with the full year
+/// * `reference` The date the age is calculated at
+///
+/// # Returns
+/// Ok if the separator matches the age, otherwise Err with the age
+pub fn separator(separator: char, date: Date, reference: NaiveDate) -> Result<(), Error> {
+    let age = date.age_at(reference);
+
+    match (separator, age >= 100) {
+        ('-', true) => Err(Error::PlusNeeded { age }),
+        ('+', false) => Err(Error::PlusNotAllowed { age }),
+        _ => Ok(()),
+    }
+}
+
+
+
+
+/// Complete a pin that is missing its control digit
+///
+/// Accepts the same shapes as [input::parse] with the last digit left out, like `850312-123`
+/// or `19850312123`. The birth date still has to pass the checks.
+///
+/// # Arguments
+/// * `partial` The pin without its control digit
+/// * `reference` The date the pin is interpreted at, see [input::parse]
+///
+/// # Returns
+/// * `Result<Pin, Error>` The completed pin or the reason it can't be completed
+pub fn complete(partial: &str, reference: NaiveDate) -> Result<Pin, Error> {
+
+    let length = partial.chars().count();
+    if !(9..=12).contains(&length) {
+        return Err(Error::InvalidLength { length, min: 9, max: 12 })
+    }
+
+    // parse with a placeholder so the shape and digits are checked like any other pin
+    let mut pin = input::parse(&format!("{}0", partial), reference)?;
+
+    let mut first = [0;9];
+    first.copy_from_slice(&pin.nums[..9]);
+    pin.nums[9] = control_digit(first);
+
+    date(pin)?;
+    future(pin.date, reference)?;
+
+    Ok(pin)
+}
+
+
+
+
 /// Does all the existing checks on a pin and returns false if any of the checks returned false.
 ///
 /// # Arguments
 ///
 /// * `pin` array of the digits in a pin.
+/// * `reference` The date the pin is interpreted at, birth dates after it are invalid
+/// * `policy` If the separator has to match the age, see [separator]
 ///
 /// # Returns
 ///
 /// Ok with no value if the pin passed all the tests.
-/// Err with a string explaining witch test failed.
-pub fn full(pin:Pin) -> Result<(), &'static str>{
+/// Err with the reason the first failing test failed.
+pub fn full(pin:Pin, reference: NaiveDate, policy: Policy) -> Result<(), Error>{
+
+    date(pin)?;
 
-    match date(pin) {
-        Err(reason) => return Err(reason),
-        _ => {}
+    future(pin.date, reference)?;
+
+    checksum(pin.nums)?;
+
+    if policy == Policy::Error {
+        separator(pin.separator(), pin.date, reference)?;
     }
 
-    if !luhns(pin.nums) {
-        return Err("Luhns")
+    Ok(())
+}
+
+
+
+
+/// Does all the checks on a organisationsnummer
+///
+/// The third digit has to be 2 or more so the number can't be mistaken for a pin, the first
+/// digit has to be a known legal form group and the number has to pass [luhns].
+///
+/// # Arguments
+///
+/// * `org` the parsed organisationsnummer.
+///
+/// # Returns
+///
+/// Ok with no value if the number passed all the tests.
+/// Err with the reason the first failing test failed.
+pub fn org(org: Org) -> Result<(), Error> {
+
+    if org.nums[2] < 2 {
+        return Err(Error::ThirdDigit { digit: org.nums[2] })
     }
 
+    if org.group.is_none() {
+        return Err(Error::UnknownGroup { digit: org.nums[0] })
+    }
+
+    checksum(org.nums)?;
+
     Ok(())
 }
@@ -0,0 +1,205 @@
+use chrono::NaiveDate;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::check;
+use crate::error::Error;
+use crate::utils::{ Date, Format, Gender };
+
+
+
+/// Weights for the modulus 11 check, used on all 10 digits
+const WEIGHTS: [u8; 10] = [4, 3, 2, 7, 6, 5, 4, 3, 2, 1];
+
+
+
+
+/// A parsed danish CPR-nummer, DDMMYY-SSSS
+#[derive(Debug, Clone, Copy)]
+pub struct Cpr {
+    pub nums: [u8; 10],
+    pub date: Date,
+}
+
+
+impl Cpr {
+    /// Run the checks that apply to every number at the given date, see [check()]
+    pub fn validate(&self, reference: NaiveDate) -> Result<(), Error> {
+        check(*self, reference)
+    }
+
+    /// Get the legal gender from the last digit, odd for male and even for female
+    pub fn gender(&self) -> Gender {
+        match self.nums[9] % 2 {
+            0 => Gender::Female,
+            _ => Gender::Male,
+        }
+    }
+
+    /// Write the number in the given format
+    ///
+    /// CPR-nummer never have the full year, so the long formats are written like the short
+    /// ones.
+    ///
+    /// # Arguments
+    /// * `format` How to write the number
+    ///
+    /// # Returns
+    /// The formatted number, for example `010190-1234`
+    pub fn format(&self, format: Format) -> String {
+        let digits = self.nums.iter().map(|n| n.to_string()).collect::<String>();
+
+        match format {
+            Format::Long | Format::Short => format!("{}-{}", &digits[..6], &digits[6..]),
+            Format::LongCompact | Format::ShortCompact => digits,
+        }
+    }
+}
+
+
+impl fmt::Display for Cpr {
+    /// Writes the number as DDMMYY-SSSS
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.format(Format::Short))
+    }
+}
+
+
+impl FromStr for Cpr {
+    type Err = Error;
+
+    /// Parse a CPR-nummer, see [parse]
+    fn from_str(s: &str) -> Result<Cpr, Self::Err> {
+        parse(s)
+    }
+}
+
+
+impl TryFrom<&str> for Cpr {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Cpr, Self::Error> {
+        value.parse()
+    }
+}
+
+
+
+
+/// Find the centry from the 7th digit, following the table of the CPR office
+///
+/// # Arguments
+/// * `digit` The 7th digit, the first of the serial number
+/// * `year` The 5th and 6th digits
+///
+/// # Returns
+/// The centry, for example `19`
+fn get_centry(digit: u8, year: u8) -> i32 {
+    match (digit, year) {
+        (0..=3, _) => 19,
+        (4 | 9, 0..=36) => 20,
+        (4 | 9, _) => 19,
+        (_, 0..=57) => 20,
+        _ => 18,
+    }
+}
+
+
+
+
+/// Parse a danish CPR-nummer
+///
+/// Accepts DDMMYY-SSSS and DDMMYYSSSS.
+///
+/// # Arguments
+/// * `input` The input string to parse
+///
+/// # Returns
+/// * `Result<Cpr, Error>` The parsed number or the reason it is invalid
+pub fn parse(input: &str) -> Result<Cpr, Error> {
+    let mut chars = input.chars().enumerate().collect::<Vec<(usize, char)>>();
+
+    match chars.len() {
+        10 => {},
+        11 => {
+            let (position, separator) = chars.remove(6);
+
+            if separator != '-' {
+                return Err(Error::InvalidSeparator { position: position + 1, found: separator })
+            }
+        },
+        length => return Err(Error::InvalidLength { length, min: 10, max: 11 }),
+    }
+
+    let mut nums = [0; 10];
+    for (i, (position, char)) in chars.iter().enumerate() {
+        match char.to_digit(10) {
+            Some(digit) => nums[i] = digit as u8,
+            None => return Err(Error::NotDigit { position: position + 1, found: *char }),
+        }
+    }
+
+    let year = nums[4] * 10 + nums[5];
+
+    Ok(Cpr {
+        nums,
+        date: Date {
+            day: (nums[0] * 10 + nums[1]) as u32,
+            month: (nums[2] * 10 + nums[3]) as u32,
+            year: get_centry(nums[6], year) * 100 + year as i32,
+        },
+    })
+}
+
+
+
+
+/// Does the checks that apply to every CPR-nummer
+///
+/// The birth date has to exist and can't be after the reference date. The control digit is
+/// checked separately by [modulus] since numbers given out after 2007 don't always pass it.
+///
+/// # Arguments
+/// * `number` The parsed number
+/// * `reference` The date the number is interpreted at
+///
+/// # Returns
+/// Ok with no value if the number passed all the tests.
+/// Err with the reason the first failing test failed.
+pub fn check(number: Cpr, reference: NaiveDate) -> Result<(), Error> {
+    check::birth_date(number.date, reference)
+}
+
+
+
+
+/// Check the control digit with the old modulus 11 rule
+///
+/// # Arguments
+/// * `number` The parsed number
+///
+/// # Returns
+/// Ok if the weighted sum of the digits is divisable by 11, otherwise Err with the last digit
+/// it should have had
+pub fn modulus(number: Cpr) -> Result<(), Error> {
+    let sum = number.nums[..9].iter()
+        .zip(WEIGHTS)
+        .map(|(num, weight)| (num * weight) as u32)
+        .sum::<u32>();
+
+    let expected = match (11 - sum % 11) % 11 {
+        10 => return Err(Error::NoControlDigit),
+        digit => digit as u8,
+    };
+
+    if number.nums[9] != expected {
+        return Err(Error::Modulus {
+            modulus: 11,
+            position: 10,
+            expected: char::from(b'0' + expected),
+            found: char::from(b'0' + number.nums[9]),
+        })
+    }
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::fmt;
+
+
+/// Reasons a number can be invalid
+///
+/// Positions are counted from 1, the same way a person would count the chars of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The input doesn't have the length of any accepted shape, which are `min` to `max` chars
+    InvalidLength { length: usize, min: usize, max: usize },
+
+    /// The separator isn't allowed for this shape
+    InvalidSeparator { position: usize, found: char },
+
+    /// A char that should be a digit isn't
+    NotDigit { position: usize, found: char },
+
+    /// The month is 0 or over 12
+    MonthOutOfRange { month: u32 },
+
+    /// The day is 0 or after the last day of the month
+    DayOutOfRange { day: u32, month: u32, year: i32 },
+
+    /// The control digit doesn't match the one calculated from the other digits
+    Checksum { expected: u8, found: u8 },
+
+    /// The 12 digit form of a organisationsnummer doesn't start with 16
+    InvalidPrefix,
+
+    /// The third digit of a organisationsnummer is under 2
+    ThirdDigit { digit: u8 },
+
+    /// The first digit of a organisationsnummer isn't a known legal form group
+    UnknownGroup { digit: u8 },
+
+    /// The date can't be written with 10 digits. i.e it is in the future or more than 200
+    /// years ago
+    DateOutOfRange,
+
+    /// The birth date is after the reference date
+    FutureDate { day: u32, month: u32, year: i32 },
+
+    /// The separator is a - but the person is 100 or older
+    PlusNeeded { age: i32 },
+
+    /// The separator is a + but the person is under 100
+    PlusNotAllowed { age: i32 },
+
+    /// The individual number of a norwegian fødselsnummer isn't given out for the year
+    IndividualNumber { individual: u16, year: u8 },
+
+    /// There is no control digit that would make the number valid
+    NoControlDigit,
+
+    /// A control char calculated with a modulus doesn't match the one in the number
+    Modulus { modulus: u8, position: usize, expected: char, found: char },
+
+    /// The start of a range is after its end
+    ReversedRange,
+
+    /// A range doesn't have enough pins to generate as many as asked for
+    RangeTooSmall { count: u64, available: u64 },
+}
+
+
+impl Error {
+    /// Short code for the error that won't change between versions
+    ///
+    /// # Returns
+    /// A lowercase code, for example `checksum`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidLength { .. } => "length",
+            Error::InvalidSeparator { .. } => "separator",
+            Error::NotDigit { .. } => "not-digit",
+            Error::MonthOutOfRange { .. } => "month",
+            Error::DayOutOfRange { .. } => "day",
+            Error::Checksum { .. } => "checksum",
+            Error::InvalidPrefix => "prefix",
+            Error::ThirdDigit { .. } => "third-digit",
+            Error::UnknownGroup { .. } => "group",
+            Error::DateOutOfRange => "date-out-of-range",
+            Error::FutureDate { .. } => "future",
+            Error::PlusNeeded { .. } => "plus-needed",
+            Error::PlusNotAllowed { .. } => "plus-not-allowed",
+            Error::IndividualNumber { .. } => "individual",
+            Error::NoControlDigit => "no-control-digit",
+            Error::Modulus { .. } => "modulus",
+            Error::ReversedRange => "reversed-range",
+            Error::RangeTooSmall { .. } => "range-too-small",
+        }
+    }
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidLength { length, min, .. } if length < min => write!(f, "Too short, {} chars", length),
+            Error::InvalidLength { length, .. } => write!(f, "Too long, {} chars", length),
+            Error::InvalidSeparator { position, found } => write!(f, "Separator '{}' not allowed at char {}", found, position),
+            Error::NotDigit { position, found } => write!(f, "'{}' at char {} is not a number", found, position),
+            Error::MonthOutOfRange { month } => write!(f, "Month {} is not 1 to 12", month),
+            Error::DayOutOfRange { day, month, year } => write!(f, "Day {} is not possible in {}-{:02}", day, year, month),
+            Error::Checksum { expected, found } => write!(f, "Luhns, control digit is {} but should be {}", found, expected),
+            Error::InvalidPrefix => write!(f, "Must start with 16"),
+            Error::ThirdDigit { digit } => write!(f, "Third digit is {} but must be 2 or more", digit),
+            Error::UnknownGroup { digit } => write!(f, "First digit {} is not a known group", digit),
+            Error::DateOutOfRange => write!(f, "Date can't be written with 10 digits"),
+            Error::FutureDate { day, month, year } => write!(f, "Born {}-{:02}-{:02}, which is in the future", year, month, day),
+            Error::PlusNeeded { age } => write!(f, "Person is {} years old, a + is needed instead of -", age),
+            Error::PlusNotAllowed { age } => write!(f, "Person is {} years old, a - is needed instead of +", age),
+            Error::IndividualNumber { individual, year } => write!(f, "Individual number {:03} is not used for the year {:02}", individual, year),
+            Error::NoControlDigit => write!(f, "No control digit can make the number valid"),
+            Error::Modulus { modulus, position, expected, found } => write!(f, "Modulus {}, control char at {} is {} but should be {}", modulus, position, found, expected),
+            Error::ReversedRange => write!(f, "Range starts after it ends"),
+            Error::RangeTooSmall { count, available } => write!(f, "Range only has {} pins but {} were asked for", available, count),
+        }
+    }
+}
+
+
+impl std::error::Error for Error {}
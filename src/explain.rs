@@ -0,0 +1,209 @@
+use chrono::prelude::*;
+
+use crate::{ check, input };
+use crate::utils::{ get_year, max_day, Format, Guess, Kind, Pin };
+
+
+
+/// Weights used by [check::full] for each of the 10 digits
+const WEIGHTS: [u8; 10] = [2, 1, 2, 1, 2, 1, 2, 1, 2, 1];
+
+
+
+
+/// Describe how the shape of the input was read
+fn shape(input: &str, chars: &[char]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let format = match Format::detect(input) {
+        Some(format) => format,
+        None => {
+            lines.push(format!("Shape: {} chars, pins are 10 to 13 chars", chars.len()));
+            return lines;
+        }
+    };
+
+    lines.push(format!("Shape: {} chars, matches {}", chars.len(), match format {
+        Format::Long => "YYYYMMDD-NNNN",
+        Format::LongCompact => "YYYYMMDDNNNN",
+        Format::Short => "YYMMDD-NNNN",
+        Format::ShortCompact => "YYMMDDNNNN",
+    }));
+
+    lines.push(match format {
+        Format::Short if chars[6] == '+' => "Separator: '+' at char 7, the person is 100 or older".to_string(),
+        Format::Short => format!("Separator: '{}' at char 7", chars[6]),
+        Format::Long => format!("Separator: '{}' at char 9", chars[8]),
+        _ => "Separator: none".to_string(),
+    });
+
+    lines
+}
+
+
+
+
+/// Describe how the centry of the birth year was found
+fn centry(input: &str, pin: Pin, reference: NaiveDate) -> String {
+    let decade = pin.date.year.rem_euclid(100);
+
+    if matches!(Format::detect(input), Some(Format::Long) | Some(Format::LongCompact)) {
+        return format!("Centry: {} is given, year {}", pin.date.year / 100, pin.date.year)
+    }
+
+    let current = reference.year() % 100;
+    let (_, guess) = get_year(decade, pin.date.month, pin.date.day, reference);
+
+    let reason = match guess {
+        Guess::After => format!(
+            "{:02} is after {:02} so it's the previous centry", decade, current),
+        Guess::Before => format!(
+            "{:02} is not after {:02} so it's the same centry", decade, current),
+        Guess::Upcoming => format!(
+            "{:02} is this year but {:02}-{:02} hasn't happened yet so it's the previous centry",
+            decade, pin.date.month, pin.date.day),
+        Guess::Passed => format!(
+            "{:02} is this year and {:02}-{:02} has happened so it's the same centry",
+            decade, pin.date.month, pin.date.day),
+    };
+
+    let plus = match pin.plus {
+        true => ", 100 years earlier because of the '+'",
+        false => "",
+    };
+
+    format!(
+        "Centry: guessed from the reference date {}, {}{}, year {}",
+        reference, reason, plus, pin.date.year)
+}
+
+
+
+
+/// Describe the date checks
+fn date(pin: Pin, reference: NaiveDate) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if pin.kind == Kind::Samordningsnummer {
+        lines.push(format!(
+            "Kind: day {} is over 60 so it's a samordningsnummer, birth day {}",
+            pin.date.day + 60, pin.date.day));
+    }
+
+    let month = pin.date.month;
+    match (1..=12).contains(&month) {
+        true => lines.push(format!("Month: {} is 1 to 12, ok", month)),
+        false => {
+            lines.push(format!("Month: {} is not 1 to 12, invalid", month));
+            return lines;
+        }
+    }
+
+    let max = max_day(month, pin.date.year);
+    let ok = match (1..=max).contains(&pin.date.day) {
+        true => "ok",
+        false => "invalid",
+    };
+    lines.push(format!(
+        "Day: {} in {}-{:02} must be 1 to {}, {}",
+        pin.date.day, pin.date.year, month, max, ok));
+
+    if let Some(born) = pin.date.naive() {
+        lines.push(match born > reference {
+            true => format!("Born: {} is after the reference date {}, invalid", born, reference),
+            false => format!("Born: {} is not after the reference date {}, ok", born, reference),
+        });
+    }
+
+    lines
+}
+
+
+
+
+/// Describe the luhn computation as a table
+fn luhns(nums: [u8; 10]) -> Vec<String> {
+    let products = (0..10).map(|i| nums[i] * WEIGHTS[i]).collect::<Vec<u8>>();
+
+    // products over 9 add their digits, 16 counts as 1 + 6
+    let sums = products.iter().map(|product| product / 10 + product % 10).collect::<Vec<u8>>();
+    let sum = sums.iter().map(|sum| *sum as u32).sum::<u32>();
+
+    let row = |name: &str, values: &[u8]| {
+        let values = values.iter().map(|value| format!("{:>3}", value)).collect::<String>();
+        format!("  {:8}{}", name, values)
+    };
+
+    let mut lines = vec![
+        "Luhn:".to_string(),
+        row("digit", &nums),
+        row("weight", &WEIGHTS),
+        row("product", &products),
+        row("sum", &sums),
+    ];
+
+    match sum % 10 {
+        0 => lines.push(format!("  total {}, divisible by 10, ok", sum)),
+        _ => {
+            let mut first = [0; 9];
+            first.copy_from_slice(&nums[..9]);
+
+            lines.push(format!(
+                "  total {}, not divisible by 10, control digit should be {}",
+                sum, check::control_digit(first)));
+        },
+    }
+
+    lines
+}
+
+
+
+
+/// Explain every step of checking a pin
+///
+/// Goes through the same steps as [input::parse], [check::full] and [check::separator] and
+/// describes the decisions made in each of them, ending with the result.
+///
+/// # Arguments
+/// * `input` The pin as it was written
+/// * `reference` The date the pin is interpreted at, see [input::parse]
+///
+/// # Returns
+/// One line per step
+pub fn explain(input: &str, reference: NaiveDate) -> Vec<String> {
+    let chars = input.chars().collect::<Vec<char>>();
+    let mut lines = shape(input, &chars);
+
+    let pin = match input::parse(input, reference) {
+        Ok(pin) => pin,
+        Err(reason) => {
+            lines.push(format!("Result: invalid - {}", reason));
+            return lines;
+        }
+    };
+
+    lines.push(centry(input, pin, reference));
+
+    // the year of the other shapes is guessed from the separator, so it always matches
+    let separator = match Format::detect(input) {
+        Some(Format::Long) => check::separator(chars[8], pin.date, reference),
+        _ => Ok(()),
+    };
+
+    if Format::detect(input) == Some(Format::Long) {
+        lines.push(match separator {
+            Ok(()) => format!("Separator: '{}' matches the age {}, ok", chars[8], pin.age_at(reference)),
+            Err(reason) => format!("Separator: {}, invalid", reason),
+        });
+    }
+    lines.extend(date(pin, reference));
+    lines.extend(luhns(pin.nums));
+
+    lines.push(match check::full(pin, reference, check::Policy::Error) {
+        Ok(()) => format!("Result: valid {}", pin.kind),
+        Err(reason) => format!("Result: invalid - {}", reason),
+    });
+
+    lines
+}
@@ -0,0 +1,199 @@
+use chrono::NaiveDate;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::check;
+use crate::error::Error;
+use crate::utils::{ Date, Gender };
+
+
+
+
+/// A parsed finnish henkilötunnus, DDMMYYCNNNX
+#[derive(Debug, Clone, Copy)]
+pub struct Hetu {
+    /// The birth date and individual number, DDMMYYNNN
+    pub nums: [u8; 9],
+
+    /// The centry sign between the birth date and individual number
+    pub sign: char,
+
+    /// The control char at the end
+    pub control: char,
+
+    pub date: Date,
+}
+
+
+impl Hetu {
+    /// Run all checks on the number at the given date, see [check()]
+    pub fn validate(&self, reference: NaiveDate) -> Result<(), Error> {
+        check(*self, reference)
+    }
+
+    /// Get the individual number, the 8th to 10th chars
+    pub fn individual(&self) -> u16 {
+        self.nums[6] as u16 * 100 + self.nums[7] as u16 * 10 + self.nums[8] as u16
+    }
+
+    /// Get the legal gender from the individual number, odd for male and even for female
+    pub fn gender(&self) -> Gender {
+        match self.individual() % 2 {
+            0 => Gender::Female,
+            _ => Gender::Male,
+        }
+    }
+}
+
+
+impl fmt::Display for Hetu {
+    /// Writes the number as DDMMYYCNNNX, there is no other format
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.nums.iter().map(|n| n.to_string()).collect::<String>();
+        f.pad(&format!("{}{}{}{}", &digits[..6], self.sign, &digits[6..], self.control))
+    }
+}
+
+
+impl FromStr for Hetu {
+    type Err = Error;
+
+    /// Parse a henkilötunnus, see [parse]
+    fn from_str(s: &str) -> Result<Hetu, Self::Err> {
+        parse(s)
+    }
+}
+
+
+impl TryFrom<&str> for Hetu {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Hetu, Self::Error> {
+        value.parse()
+    }
+}
+
+
+
+
+/// Find the centry from the sign between the birth date and individual number
+///
+/// `+` is used for 1800, `-` and `U` to `Y` for 1900 and `A` to `F` for 2000.
+///
+/// # Arguments
+/// * `sign` The 7th char
+///
+/// # Returns
+/// The centry, for example `19`, or None if the char isn't a centry sign
+fn get_centry(sign: char) -> Option<i32> {
+    match sign {
+        '+' => Some(18),
+        '-' | 'U'..='Y' => Some(19),
+        'A'..='F' => Some(20),
+        _ => None,
+    }
+}
+
+
+
+
+/// Parse a finnish henkilötunnus
+///
+/// The number is always 11 chars, the birth date, a centry sign, a three digit individual
+/// number and a control char that can be a digit or a letter.
+///
+/// # Arguments
+/// * `input` The input string to parse
+///
+/// # Returns
+/// * `Result<Hetu, Error>` The parsed number or the reason it is invalid
+pub fn parse(input: &str) -> Result<Hetu, Error> {
+    let chars = input.chars().collect::<Vec<char>>();
+
+    if chars.len() != 11 {
+        return Err(Error::InvalidLength { length: chars.len(), min: 11, max: 11 })
+    }
+
+    let sign = chars[6];
+    let centry = get_centry(sign).ok_or(Error::InvalidSeparator { position: 7, found: sign })?;
+
+    let mut nums = [0; 9];
+    for (i, position) in (0..6).chain(7..10).enumerate() {
+        match chars[position].to_digit(10) {
+            Some(digit) => nums[i] = digit as u8,
+            None => return Err(Error::NotDigit { position: position + 1, found: chars[position] }),
+        }
+    }
+
+    let year = nums[4] * 10 + nums[5];
+
+    Ok(Hetu {
+        nums,
+        sign,
+        control: chars[10],
+        date: Date {
+            day: (nums[0] * 10 + nums[1]) as u32,
+            month: (nums[2] * 10 + nums[3]) as u32,
+            year: centry * 100 + year as i32,
+        },
+    })
+}
+
+
+
+
+/// Does all the checks on a henkilötunnus
+///
+/// The birth date has to exist and can't be after the reference date, individual numbers 000
+/// and 001 are never given out and the control char has to match [check::mod31].
+///
+/// # Arguments
+/// * `number` The parsed number
+/// * `reference` The date the number is interpreted at
+///
+/// # Returns
+/// Ok with no value if the number passed all the tests.
+/// Err with the reason the first failing test failed.
+pub fn check(number: Hetu, reference: NaiveDate) -> Result<(), Error> {
+
+    check::birth_date(number.date, reference)?;
+
+    let individual = number.individual();
+    if individual < 2 {
+        return Err(Error::IndividualNumber { individual, year: (number.date.year % 100) as u8 })
+    }
+
+    let expected = check::mod31(number.nums);
+    if number.control != expected {
+        return Err(Error::Modulus {
+            modulus: 31,
+            position: 11,
+            expected,
+            found: number.control,
+        })
+    }
+
+    Ok(())
+}
+
+
+
+
+/// Check if an input looks like a henkilötunnus rather than a swedish number
+///
+/// Letter centry signs are never used in swedish pins. Numbers with `-` or `+` look just like
+/// swedish pins written as YYMMDD-NNNN, unless the control char is a letter since swedish pins
+/// always end with a digit.
+pub fn detect(input: &str) -> bool {
+    let chars = input.chars().collect::<Vec<char>>();
+
+    if chars.len() != 11 {
+        return false
+    }
+
+    match chars[6] {
+        'A'..='F' | 'U'..='Y' => true,
+        '-' | '+' => !chars[10].is_ascii_digit(),
+        _ => false,
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::prelude::*;
+
+use crate::check::control_digit;
+use crate::error::Error;
+use crate::utils::{ get_date, Date, Kind, Pin };
+
+
+
+
+
+/// Create a valid pin for the given birth date and serial number
+///
+/// The control digit is calculated with [control_digit]. The plus flag is set the same way
+/// [crate::input::parse] would interpret it, meaning it is only set if the 10 digit form would
+/// otherwise be read as someone born 100 years later.
+///
+/// # Arguments
+/// * `date` The birth date of the pin
+/// * `serial` The three digit serial number, 0 to 999
+/// * `reference` The date the pin is written at, decides if a plus is needed
+///
+/// # Returns
+/// * `Result<Pin, Error>` The generated pin or Err if the date can't be written in
+///   the 10 digit form. i.e it is after `reference` or more than 200 years before it
+pub fn pin(date: NaiveDate, serial: u32, reference: NaiveDate) -> Result<Pin, Error> {
+
+    let year = date.year();
+    let decade = year.rem_euclid(100) as u32;
+
+    let digits = [
+        decade / 10, decade % 10,               // OOxxxx-xxxx
+        date.month() / 10, date.month() % 10,   // xxOOxx-xxxx
+        date.day() / 10, date.day() % 10,       // xxxxOO-xxxx
+        serial / 100 % 10, serial / 10 % 10, serial % 10, // xxxxxx-OOOx
+    ];
+
+    // define array to store results in
+    let mut first = [0;9];
+    for (i, digit) in digits.iter().enumerate() {
+        first[i] = *digit as u8;
+    }
+
+    let mut nums = [0;10];
+    nums[..9].copy_from_slice(&first);
+    nums[9] = control_digit(first);
+
+
+    // check what year the parser would guess without a plus
+    let guess = get_date(nums, false, None, reference).year;
+
+    let plus = match guess - year {
+        0 => false,
+        100 => true,
+        _ => return Err(Error::DateOutOfRange)
+    };
+
+    Ok(Pin {
+        nums,
+        plus,
+        date: Date {
+            year,
+            month: date.month(),
+            day: date.day(),
+        },
+        kind: Kind::Personnummer,
+    })
+}
+
+
+
+
+/// Generate `count` valid pins with birth dates between `from` and `to`
+///
+/// The pins are spread evenly over the range, and dates are only reused once every other date
+/// in the range has been used. Serial numbers step through 001 to 999 so following pins
+/// alternate between female and male, and a date never gets the same serial twice.
+///
+/// # Arguments
+/// * `from` First birth date, inclusive
+/// * `to` Last birth date, inclusive
+/// * `count` Number of pins to generate
+/// * `reference` The date the pins are written at, see [pin]
+///
+/// # Returns
+/// * `Result<impl Iterator<Item=Pin>, Error>` The pins, or Err if `from` is after `to` or
+///   the range is too small to fit `count` pins
+pub fn range(
+    from: NaiveDate,
+    to: NaiveDate,
+    count: u32,
+    reference: NaiveDate,
+) -> Result<impl Iterator<Item=Pin>, Error> {
+
+    if from > to {
+        return Err(Error::ReversedRange)
+    }
+
+    // if both ends can be written all dates in between can be as well
+    pin(from, 1, reference)?;
+    pin(to, 1, reference)?;
+
+    let days = (to - from).num_days() as u64 + 1;
+    let count = count as u64;
+
+    if count > days * 999 {
+        return Err(Error::RangeTooSmall { count, available: days * 999 })
+    }
+
+    Ok((0..count).map(move |i| {
+        let offset = match count <= days {
+            true => i * days / count,
+            false => i % days,
+        };
+
+        // each round over the dates shifts the serials by one, so they differ for every date
+        let serial = (i % days + i / days) % 999 + 1;
+
+        let date = from + chrono::Duration::days(offset as i64);
+
+        // both ends of the range were checked above
+        pin(date, serial as u32, reference).unwrap()
+    }))
+}
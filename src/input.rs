@@ -1,20 +1,21 @@
-use crate::utils::{ get_date, Pin };
+use chrono::NaiveDate;
+use std::fmt;
 
-/// Convert a given vec of chars into the numbers they represent.
+use crate::{ denmark, finland, norway };
+use crate::error::Error;
+use crate::utils::{ get_date, get_kind, get_group, Country, Identity, Org, Pin };
+
+/// Convert 10 chars into the numbers they represent.
 ///
 /// `chars` has to be 10 elements long. Or the function will panic.
-/// If any of the chars given are not valid integer char codes, the function will return Err
-/// If `plus` is true and the parser didn't fail for other reasons, the year returned for the
-/// date will be reduced by 100. i.e the person is 100 years older than expected
 ///
 /// # Arguments
-/// * `chars` slice of char codes
-/// * `plus` Wether or not a plus was used instead of a minus.
+/// * `chars` slice of char codes, together with their position in the input
 ///
 /// # Returns
-/// * `Result<Pin, &'static str>` Either a parsed Pin or a Err if any of the given
-/// chars weren't numbers
-fn parse_10(chars: Vec<char>, plus: bool) -> Result<Pin, &'static str> {
+/// * `Result<[u8;10], Error>` The numbers or Err if any of the given chars weren't
+///   numbers
+fn digits(chars: &[(usize, char)]) -> Result<[u8;10], Error> {
 
     // panic if `chars is not 10 long`
     assert_eq!(chars.len(), 10);
@@ -24,26 +25,59 @@ fn parse_10(chars: Vec<char>, plus: bool) -> Result<Pin, &'static str> {
 
     for i in 0..10 {
         // get the digit the char represents
-        let char = chars[i];
+        let (position, char) = chars[i];
         let digit = char.to_digit(10);
 
         match digit {
             Some(dig) => {
                 result[i] = dig.try_into().unwrap()
             },
-            None => return Err("Not all numbers")
+            None => return Err(Error::NotDigit { position: position + 1, found: char })
         };
     };
 
+    Ok(result)
+}
+
+
+
+
+
+
+
+
+
+
+/// Convert a given vec of chars into the numbers they represent.
+///
+/// `chars` has to be 10 elements long. Or the function will panic.
+/// If any of the chars given are not valid integer char codes, the function will return Err
+/// If `plus` is true and the parser didn't fail for other reasons, the year returned for the
+/// date will be reduced by 100. i.e the person is 100 years older than expected
+///
+/// # Arguments
+/// * `chars` slice of char codes, together with their position in the input
+/// * `plus` Wether or not a plus was used instead of a minus.
+/// * `reference` The date used to guess the centry
+///
+/// # Returns
+/// * `Result<Pin, Error>` Either a parsed Pin or a Err if any of the given
+///   chars weren't numbers
+fn parse_10(chars: Vec<(usize, char)>, plus: bool, reference: NaiveDate) -> Result<Pin, Error> {
+
+    let result = digits(&chars)?;
+
     // Create parsed pin
-    return Ok(Pin {
+    Ok(Pin {
         nums: result,
         plus,
         date: get_date(
             result,
             plus,
-            None
-        )
+            None,
+            reference,
+        ),
+        kind: get_kind(result),
     })
 }
 
@@ -61,40 +95,26 @@ fn parse_10(chars: Vec<char>, plus: bool) -> Result<Pin, &'static str> {
 /// `chars` has to be 12 elements long. Or the function will panic.
 /// If any of the chars given are not valid integer char codes, the function will return Err
 ///
-/// Since the full year is given no flag for if a plus was used is required.
+/// Since the full year is given no flag for if a plus was used is required. The plus flag is set
+/// if the person is 100 or older at the reference date, like it would be in YYMMDD+NNNN.
 ///
 /// # Arguments
-/// * `chars` slice of char codes
+/// * `chars` slice of char codes, together with their position in the input
+/// * `reference` The date the pin is interpreted at
 ///
 /// # Returns
-/// * `Result<Pin, &'static str>` Either a parsed Pin or a Err if any of the given
-/// chars weren't numbers
-fn parse_12(chars: Vec<char>) -> Result<Pin, &'static str> {
+/// * `Result<Pin, Error>` Either a parsed Pin or a Err if any of the given
+///   chars weren't numbers
+fn parse_12(chars: Vec<(usize, char)>, reference: NaiveDate) -> Result<Pin, Error> {
 
-    // panic if `chars is not 10 long`
+    // panic if `chars is not 12 long`
     assert_eq!(chars.len(), 12);
 
-    // define array to store results in
-    let mut result = [0;10];
-
-    for i in 0..10 {
-        // get the digit the char represents
-        let char = chars[i+2];
-        let digit = char.to_digit(10);
-
-        match digit {
-            Some(dig) => {
-                result[i] = dig.try_into().unwrap()
-            },
-            None => return Err("Not all numbers")
-        };
-    };
-    
-
-    let digit = chars[0].to_digit(10);
+    let (position, char) = chars[0];
+    let digit = char.to_digit(10);
 
     if digit.is_none() {
-        return Err("Not all numbers")
+        return Err(Error::NotDigit { position: position + 1, found: char })
     }
 
     let millenia: i32 = digit.unwrap().try_into().unwrap();
@@ -102,14 +122,17 @@ fn parse_12(chars: Vec<char>) -> Result<Pin, &'static str> {
 
 
 
-    let digit = chars[1].to_digit(10);
+    let (position, char) = chars[1];
+    let digit = char.to_digit(10);
 
     if digit.is_none() {
-        return Err("Not all numbers")
+        return Err(Error::NotDigit { position: position + 1, found: char })
     }
 
     let centry: i32 = digit.unwrap().try_into().unwrap();
 
+    let result = digits(&chars[2..])?;
+
 
 
 
@@ -117,15 +140,14 @@ fn parse_12(chars: Vec<char>) -> Result<Pin, &'static str> {
     let centry = millenia*10 + centry;
 
 
-    // Create parsed pin
-    return Ok(Pin {
+    let date = get_date(result, false, Some(centry), reference);
+
+    // Create parsed pin, no separator is written so it gets the one it should have
+    Ok(Pin {
         nums: result,
-        plus: false,
-        date: get_date(
-            result,
-            false,
-            Some(centry)
-        )
+        plus: date.age_at(reference) >= 100,
+        date,
+        kind: get_kind(result),
     })
 }
 
@@ -142,51 +164,316 @@ fn parse_12(chars: Vec<char>) -> Result<Pin, &'static str> {
 ///
 /// # Arguments
 /// * `input` The input string to parse
-/// 
+/// * `reference` The date the pin is interpreted at, used to guess the centry of 10 digit pins
+///
 /// # Returns
-/// * `Result<[u8;10], &'static str>` Array of numbers or a message explaining why pin is
-/// invalid
-pub fn parse(input: &String) -> Result<Pin, &'static str> {
-    let mut chars = input.chars().collect::<Vec<char>>();
+/// * `Result<Pin, Error>` The parsed pin or the reason the pin is invalid
+pub fn parse(input: &str, reference: NaiveDate) -> Result<Pin, Error> {
+    let mut chars = input.chars().enumerate().collect::<Vec<(usize, char)>>();
 
-    return match chars.len() {
-        10 => parse_10(chars, false),
+    match chars.len() {
+        10 => parse_10(chars, false, reference),
         11 => {
             // extract the 7th char
-            let extra = chars.remove(6);
-            
+            let (position, extra) = chars.remove(6);
+
             // check for - or + in the 7th spot
-            if extra != '-' && extra != '+' { 
-                return Err("7th char must be - or +")
+            if extra != '-' && extra != '+' {
+                return Err(Error::InvalidSeparator { position: position + 1, found: extra })
             }
 
             // parse numbers
-            parse_10(chars, extra == '+')
+            parse_10(chars, extra == '+', reference)
         }
 
-        12 => parse_12(chars),
+        12 => parse_12(chars, reference),
         13 => {
             // extract the 9th char
-            let extra = chars.remove(8);
+            let (position, extra) = chars.remove(8);
 
-            // check if the 9th spot is a -
-            // since we are given the full year, plus can't be used
-            if extra != '-' { 
-                return Err("9th char must be -")
+            // check for - or + in the 9th spot
+            // the full year is given so the separator doesn't change the year, [check::full]
+            // makes sure it matches the age
+            if extra != '-' && extra != '+' {
+                return Err(Error::InvalidSeparator { position: position + 1, found: extra })
             }
 
             // parse numbers
-            parse_12(chars)
+            let mut pin = parse_12(chars, reference)?;
+            pin.plus = extra == '+';
+
+            Ok(pin)
         }
 
-        _ => {
-            // Length is invalid
-            
-            if input.len() > 11 {
-                Err("Too long")
-            } else {
-                Err("Too short")
-            }
+        // Length is invalid
+        length => Err(Error::InvalidLength { length, min: 10, max: 13 }),
+    }
+}
+
+
+
+
+
+
+
+
+
+
+/// Parses a given input string as a organisationsnummer
+///
+/// Accepts the same shapes as [parse] except that the separator can only be a -, and the 12
+/// digit form has to start with 16 instead of a centry.
+///
+/// # Arguments
+/// * `input` The input string to parse
+///
+/// # Returns
+/// * `Result<Org, Error>` The parsed number or the reason it is invalid
+pub fn parse_org(input: &str) -> Result<Org, Error> {
+    let mut chars = input.chars().enumerate().collect::<Vec<(usize, char)>>();
+
+    // remove the 16 prefix from the 12 digit forms
+    if chars.len() == 12 || chars.len() == 13 {
+        if chars[0].1 != '1' || chars[1].1 != '6' {
+            return Err(Error::InvalidPrefix)
+        }
+
+        chars.drain(..2);
+    }
+
+    if chars.len() == 11 {
+        // extract the 7th char
+        let (position, extra) = chars.remove(6);
+
+        // check for - in the 7th spot
+        if extra != '-' {
+            return Err(Error::InvalidSeparator { position: position + 1, found: extra })
         }
     }
+
+    if chars.len() != 10 {
+        // Length is invalid
+        return Err(Error::InvalidLength { length: input.chars().count(), min: 10, max: 13 })
+    }
+
+    let result = digits(&chars)?;
+
+    Ok(Org {
+        nums: result,
+        group: get_group(result),
+    })
+}
+
+
+
+
+
+
+
+
+
+
+/// Parses a given input string as either a pin or a organisationsnummer
+///
+/// Organisationsnummer always have a third digit of 2 or more, which is impossible for pins
+/// since it is the first digit of the month. Anything that can't be parsed as a
+/// organisationsnummer with such a digit is parsed as a pin.
+///
+/// # Arguments
+/// * `input` The input string to parse
+/// * `reference` The date a pin is interpreted at, see [parse]
+///
+/// # Returns
+/// * `Result<Identity, Error>` The parsed number or the reason it is invalid
+pub fn parse_swedish(input: &str, reference: NaiveDate) -> Result<Identity, Error> {
+    match parse_org(input) {
+        Ok(org) if org.nums[2] >= 2 => Ok(Identity::Organisation(org)),
+        _ => parse(input, reference).map(Identity::Person),
+    }
+}
+
+
+
+
+/// Parses a given input string as a number from the given country
+///
+/// Swedish numbers can be either pins or organisationsnummer, see [parse_swedish].
+///
+/// # Arguments
+/// * `input` The input string to parse
+/// * `country` The country the number is from
+/// * `reference` The date the number is interpreted at, see [parse]
+///
+/// # Returns
+/// * `Result<Identity, Error>` The parsed number or the reason it is invalid
+pub fn parse_country(input: &str, country: Country, reference: NaiveDate) -> Result<Identity, Error> {
+    match country {
+        Country::Se => parse_swedish(input, reference),
+        Country::No => norway::parse(input).map(Identity::Norwegian),
+        Country::Dk => denmark::parse(input).map(Identity::Danish),
+        Country::Fi => finland::parse(input).map(Identity::Finnish),
+    }
+}
+
+
+
+
+/// Parses a given input string as a number from any country that can be detected from its shape
+///
+/// Danish numbers are never detected since they look just like swedish pins, see
+/// [Country::detect].
+///
+/// # Arguments
+/// * `input` The input string to parse
+/// * `reference` The date the number is interpreted at, see [parse]
+///
+/// # Returns
+/// * `Result<Identity, Error>` The parsed number or the reason it is invalid
+pub fn parse_any(input: &str, reference: NaiveDate) -> Result<Identity, Error> {
+    parse_country(input, Country::detect(input), reference)
+}
+
+
+
+
+
+
+
+
+
+
+/// Something [normalize] changed in an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// Spaces, tabs and carriage returns were removed
+    Whitespace,
+
+    /// Dots were removed, like in 850312.1234
+    Dots,
+
+    /// Unicode dashes such as en dash and minus sign were replaced with -
+    Dashes,
+
+    /// Full-width digits or plus signs were replaced with ascii ones
+    FullWidth,
+
+    /// Digits of other scripts, like arabic-indic or devanagari, were replaced with ascii ones
+    Digits,
+}
+
+
+impl Change {
+    /// Short name of the change that won't change between versions
+    pub fn code(&self) -> &'static str {
+        match self {
+            Change::Whitespace => "whitespace",
+            Change::Dots => "dots",
+            Change::Dashes => "dashes",
+            Change::FullWidth => "full-width",
+            Change::Digits => "digits",
+        }
+    }
+}
+
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Change::Whitespace => "removed whitespace",
+            Change::Dots => "removed dots",
+            Change::Dashes => "replaced unicode dashes",
+            Change::FullWidth => "replaced full-width chars",
+            Change::Digits => "replaced unicode digits",
+        })
+    }
+}
+
+
+
+
+/// The zero of every run of ten unicode decimal digits except ascii and full-width, taken from
+/// the Nd category of unicode 14
+const DIGIT_ZEROS: [u32; 64] = [
+    0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0,
+    0x1A80, 0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0,
+    0xAA50, 0xABF0, 0x104A0, 0x10D30, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450,
+    0x114D0, 0x11650, 0x116C0, 0x11730, 0x118E0, 0x11950, 0x11C50, 0x11D50, 0x11DA0, 0x16A60,
+    0x16AC0, 0x16B50, 0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E950,
+    0x1FBF0,
+];
+
+
+
+
+/// Get the value of a decimal digit from another script, see [DIGIT_ZEROS]
+fn unicode_digit(char: char) -> Option<u8> {
+    let char = char as u32;
+
+    DIGIT_ZEROS.iter()
+        .find(|zero| (**zero..**zero + 10).contains(&char))
+        .map(|zero| (char - zero) as u8)
+}
+
+
+
+
+/// Clean up noise that is common in numbers copied from documents and spreadsheets
+///
+/// Whitespace and dots are removed, unicode dashes are replaced with - and full-width digits
+/// and plus signs with ascii ones. Decimal digits of every other script in unicode are replaced
+/// with ascii digits as well. The result can then be given to [parse], [parse_org] or
+/// [parse_any].
+///
+/// # Arguments
+/// * `input` The number as it was written
+///
+/// # Returns
+/// The cleaned up number and what was changed, in the order the changes are listed in
+/// [Change]
+pub fn normalize(input: &str) -> (String, Vec<Change>) {
+    let mut result = String::with_capacity(input.len());
+    let mut changes = Vec::new();
+
+    for char in input.chars() {
+        let change = match char {
+            c if c.is_whitespace() => Change::Whitespace,
+            '.' => Change::Dots,
+
+            // hyphen, non-breaking hyphen, figure dash, en dash, em dash, minus sign, small and
+            // full-width hyphen-minus
+            '\u{2010}'..='\u{2014}' | '\u{2212}' | '\u{FE63}' | '\u{FF0D}' => {
+                result.push('-');
+                Change::Dashes
+            },
+
+            '\u{FF10}'..='\u{FF19}' => {
+                result.push(char::from(b'0' + (char as u32 - 0xFF10) as u8));
+                Change::FullWidth
+            },
+
+            '\u{FF0B}' => {
+                result.push('+');
+                Change::FullWidth
+            },
+
+            c if unicode_digit(c).is_some() => {
+                result.push(char::from(b'0' + unicode_digit(c).unwrap()));
+                Change::Digits
+            },
+
+            _ => {
+                result.push(char);
+                continue;
+            },
+        };
+
+        if !changes.contains(&change) {
+            changes.push(change);
+        }
+    }
+
+    changes.sort_by_key(|change| *change as u8);
+
+    (result, changes)
 }
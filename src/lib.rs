@@ -0,0 +1,27 @@
+//! Library for checking and generating swedish personal identity numbers (personnummer),
+//! samordningsnummer and organisationsnummer, and checking norwegian fødselsnummer, danish
+//! CPR-nummer and finnish henkilötunnus.
+//!
+//! ```
+//! use pin::Pin;
+//!
+//! let pin: Pin = "201001012382".parse().unwrap();
+//! assert!(pin.validate(pin::utils::today()).is_ok());
+//! ```
+
+pub mod check;
+pub mod denmark;
+pub mod error;
+pub mod explain;
+pub mod finland;
+pub mod generate;
+pub mod input;
+pub mod norway;
+pub mod pseudonym;
+pub mod scan;
+pub mod suggest;
+pub mod utils;
+mod testing;
+
+pub use error::Error;
+pub use utils::{ Country, Date, Format, Gender, Group, Identity, Kind, Org, Pin };
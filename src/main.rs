@@ -1,10 +1,27 @@
-use clap::{ Parser, ArgAction };
-use std::io;
+use chrono::prelude::*;
+use clap::{ Parser, Subcommand, ValueEnum, ArgAction };
+use std::fmt::Display;
+use std::fs;
+use std::io::{ self, Read, Write };
+use std::path::{ Path, PathBuf };
+use std::process::ExitCode;
 
-mod check;
-mod input;
-mod utils;
-mod testing;
+use pin::{ check, denmark, generate, input, pseudonym, scan, utils, Error, Format, Gender, Identity };
+use pin::input::Change;
+use pin::scan::Style;
+
+mod report;
+mod table;
+
+use report::{ Output, Report };
+
+
+
+/// Exit code when any number is invalid
+const INVALID: u8 = 1;
+
+/// Exit code for usage and io errors, same as clap uses for bad arguments
+const FAILED: u8 = 2;
 
 
 
@@ -15,8 +32,16 @@ mod testing;
     about="cli tool for checking and generating swedish personal identity numbers",
 )]
 struct Cli {
-    /// Omit to use stdin
-    input: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Numbers to check, omit along with --file to use stdin
+    inputs: Vec<String>,
+
+    /// File with one number per line, can be given more than once and can be a glob like
+    /// "data/*.txt"
+    #[arg(short, long = "file", conflicts_with = "csv")]
+    files: Vec<String>,
 
     /// Hide valid pins
     #[arg(short, long, default_value_t = true, action=ArgAction::SetFalse)]
@@ -37,122 +62,1047 @@ struct Cli {
     /// If results should be written in a machine friendly way
     #[arg(short, long, default_value_t = false)]
     porcelain: bool,
+
+    /// Only accept numbers written exactly as they should be, without removing whitespace and
+    /// dots or replacing unicode dashes and digits
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
+    /// What to do when the separator of YYYYMMDD-NNNN doesn't match the age, a + should only be
+    /// used for people 100 or older
+    #[arg(long, value_enum, default_value_t = Policy::Error)]
+    separator_policy: Policy,
+
+    /// Also check the modulus 11 control digit of danish CPR-nummer, numbers given out after
+    /// 2007 don't always pass it
+    #[arg(long, default_value_t = false)]
+    cpr_modulus: bool,
+
+    /// Stop at the first invalid number
+    #[arg(long, default_value_t = false)]
+    fail_fast: bool,
+
+    /// Suggest valid pins that differ from invalid ones by a single typo
+    #[arg(long, default_value_t = false)]
+    suggest: bool,
+
+    /// Explain each step of checking every pin
+    #[arg(long, default_value_t = false)]
+    explain: bool,
+
+    /// How results should be written
+    #[arg(short, long, value_enum, default_value_t = Output::Text)]
+    output: Output,
+
+    /// Read csv from stdin and write it back with the results of checking one column appended
+    #[arg(long, default_value_t = false)]
+    csv: bool,
+
+    /// Name of the csv column to check, or its position counted from 1
+    #[arg(long, default_value = "1", requires = "csv")]
+    column: String,
+
+    /// Char separating the csv columns
+    #[arg(long, default_value_t = ',', requires = "csv")]
+    delimiter: char,
+
+    /// The first csv row is not a header
+    #[arg(long = "no-header", default_value_t = true, action=ArgAction::SetFalse, requires = "csv")]
+    header: bool,
+
+    /// What country the numbers are from
+    #[arg(long, value_enum, default_value_t = Country::Se)]
+    country: Country,
+
+    /// What kind of swedish numbers to check
+    #[arg(short, long, value_enum, default_value_t = Mode::Pin)]
+    mode: Mode,
+
+    /// Only show valid pins of this gender, female or male
+    #[arg(short, long)]
+    gender: Option<Gender>,
+
+    /// Only show valid pins of people at least this old at the reference date
+    #[arg(long)]
+    min_age: Option<i32>,
+
+    /// Only show valid pins of people at most this old at the reference date
+    #[arg(long)]
+    max_age: Option<i32>,
+
+    /// Only show valid pins of people born before this date, YYYY-MM-DD
+    #[arg(long)]
+    born_before: Option<NaiveDate>,
+
+    /// Only show valid pins of people born after this date, YYYY-MM-DD
+    #[arg(long)]
+    born_after: Option<NaiveDate>,
+
+    /// Date to interpret pins at, YYYY-MM-DD [default: today]
+    #[arg(long, global = true)]
+    reference_date: Option<NaiveDate>,
+
+    /// Write valid numbers as YYYYMMDD-NNNN, YYYYMMDDNNNN, YYMMDD-NNNN or YYMMDDNNNN instead of
+    /// how they were given
+    #[arg(short = 'F', long, global = true)]
+    format: Option<Format>,
+}
+
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Personnummer and samordningsnummer
+    Pin,
+
+    /// Organisationsnummer
+    Org,
+
+    /// Detect the kind of each number
+    Auto,
+}
+
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Country {
+    /// Sweden, personnummer, samordningsnummer and organisationsnummer
+    Se,
+
+    /// Norway, fødselsnummer, d-nummer and h-nummer
+    No,
+
+    /// Denmark, CPR-nummer. Never detected by auto since they look like swedish pins
+    Dk,
+
+    /// Finland, henkilötunnus. Detected by auto when the centry sign or control char is a letter
+    Fi,
+
+    /// Detect the country of each number from its shape
+    Auto,
+}
+
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Policy {
+    /// Numbers with the wrong separator are invalid
+    Error,
+
+    /// Numbers with the wrong separator are valid, but a warning is written to stderr
+    Warn,
+}
+
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate valid pins
+    Generate {
+        /// Birth date to generate pins for, YYYY-MM-DD
+        #[arg(short, long, conflicts_with_all = ["from", "to"])]
+        date: Option<NaiveDate>,
+
+        /// First birth date to generate pins for, YYYY-MM-DD [default: 1900-01-01]
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last birth date to generate pins for, YYYY-MM-DD [default: reference date]
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Number of pins to generate
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: u32,
+
+        /// Write pins with the full year, same as --format YYYYMMDDNNNN
+        #[arg(short, long, default_value_t = false)]
+        long: bool,
+    },
+
+    /// Find valid pins in text files
+    Scan {
+        /// Files or directories to search, omit to use stdin
+        paths: Vec<PathBuf>,
+    },
+
+    /// Mask every valid pin in a text, keeping everything else as is
+    Redact {
+        /// File to redact, omit to use stdin
+        path: Option<PathBuf>,
+
+        /// How to mask pins, full, birth-date or last-four
+        #[arg(short, long, default_value = "full")]
+        style: Style,
+    },
+
+    /// Calculate the control digit of pins missing their last digit, like 850312-123
+    Checkdigit {
+        /// Pin without its control digit, omit to read one per line from stdin
+        input: Option<String>,
+    },
+
+    /// Replace each pin with a fake but valid one with the same birth year and gender
+    Pseudonymize {
+        /// File with the secret key, the same key always gives the same fake pins
+        #[arg(short, long)]
+        key_file: PathBuf,
+
+        /// File with one pin per line, omit to use stdin
+        path: Option<PathBuf>,
+    },
 }
 
 
 fn check_pin(
     pin: String,
-) -> Result<(), &'static str> {
-
-    let parsed = input::parse(&pin);
+    args: &Cli,
+    reference: NaiveDate,
+) -> Result<Identity, Error> {
 
     // Check if pin has valid format
-    if let Err(reason) = parsed {
-        return Err(reason);
+    let parsed = match (country(&pin, args), args.mode) {
+        (utils::Country::Se, Mode::Pin) => Identity::Person(input::parse(&pin, reference)?),
+        (utils::Country::Se, Mode::Org) => Identity::Organisation(input::parse_org(&pin)?),
+        (country, _) => input::parse_country(&pin, country, reference)?,
+    };
+
+    // Check if pin is valid, a separator that doesn't match the age is only a warning with
+    // --separator-policy warn
+    match (parsed, args.separator_policy) {
+        (Identity::Person(person), Policy::Warn) => {
+            check::full(person, reference, check::Policy::Ignore)?;
+
+            if let Err(reason) = check::separator(person.separator(), person.date, reference) {
+                eprintln!("{}: warning - {}", pin, reason);
+            }
+        },
+        _ => parsed.validate(reference)?,
     }
 
+    // Old CPR-nummer also have a control digit
+    if let (Identity::Danish(number), true) = (parsed, args.cpr_modulus) {
+        denmark::modulus(number)?;
+    }
 
-    let checked = check::full(parsed.unwrap());
+    // All good
+    Ok(parsed)
+}
 
-    // Check if pin is valid
-    if let Err(reason) = checked {
-        return Err(reason);
+/// Clean up a number unless --strict is given
+fn normalize(input: &str, args: &Cli) -> (String, Vec<Change>) {
+    match args.strict {
+        true => (input.to_string(), Vec::new()),
+        false => input::normalize(input),
     }
+}
 
 
-    // All good
+
+/// The country a number should be checked as, with --country auto resolved
+fn country(pin: &str, args: &Cli) -> utils::Country {
+    match args.country {
+        Country::Se => utils::Country::Se,
+        Country::No => utils::Country::No,
+        Country::Dk => utils::Country::Dk,
+        Country::Fi => utils::Country::Fi,
+        Country::Auto => utils::Country::detect(pin),
+    }
+}
+
+
+
+/// Check a number and write the result
+fn check_and_report(report: &mut Report, args: &Cli, input: &str, reference: NaiveDate) {
+    let (pin, changes) = normalize(input, args);
+
+    match check_pin(pin.clone(), args, reference) {
+        Ok(parsed) if wanted(&parsed, args, reference) => {
+            report.valid(input, &pin, &changes, parsed)
+        },
+        Ok(_) => report.filtered(),
+        Err(reason) => {
+            report.invalid(input, &pin, &changes, reason)
+        }
+    }
+}
+
+
+
+/// Find the files a --file argument refers to
+///
+/// # Arguments
+/// * `pattern` A path or a glob pattern
+///
+/// # Returns
+/// The matching files in order, or Err if the pattern is invalid or matches nothing
+fn files(pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let paths = glob::glob(pattern)
+        .map_err(|err| format!("{}: {}", pattern, err))?
+        .collect::<Result<Vec<PathBuf>, _>>()
+        .map_err(|err| err.to_string())?;
+
+    match paths.is_empty() {
+        true => Err(format!("{}: No such file", pattern)),
+        false => Ok(paths),
+    }
+}
+
+
+
+/// Check if a valid number passes the filters given as arguments
+fn wanted(parsed: &Identity, args: &Cli, reference: NaiveDate) -> bool {
+
+    if args.gender.is_some() && parsed.gender() != args.gender {
+        return false
+    }
+
+    let filters_date = args.min_age.is_some()
+        || args.max_age.is_some()
+        || args.born_before.is_some()
+        || args.born_after.is_some();
+
+    if !filters_date {
+        return true
+    }
+
+    // only pins have a birth date
+    let date = match parsed.date() {
+        Some(date) => date,
+        None => return false,
+    };
+
+    let age = date.age_at(reference);
+    let born = date.naive();
+
+    if args.min_age.is_some_and(|min| age < min) {
+        return false
+    }
+
+    if args.max_age.is_some_and(|max| age > max) {
+        return false
+    }
+
+    if args.born_before.is_some_and(|before| born >= Some(before)) {
+        return false
+    }
+
+    if args.born_after.is_some_and(|after| born <= Some(after)) {
+        return false
+    }
+
+    true
+}
+
+fn generate(
+    date: Option<NaiveDate>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    count: u32,
+    format: Format,
+    reference: NaiveDate,
+) -> Result<(), Error> {
+
+    let from = date.or(from).unwrap_or(NaiveDate::from_ymd_opt(1900, 1, 1).unwrap());
+    let to = date.or(to).unwrap_or(reference);
+
+    for pin in generate::range(from, to, count, reference)? {
+        println!("{}", pin.format(format, reference));
+    }
+
     Ok(())
 }
 
-fn invalid(pin: String, reason: &str, args: &Cli) {
-   if !args.invalid { return; }
 
-   println!(
-       "{}",
-       match args.porcelain {
-           true => {
-               match args.reason {
-                   true  => format!("! | {:^25} | {:15}", reason, pin),
-                   false => format!("! {}", pin),
-               }
-           },
 
-           false => {
-               match args.reason {
-                   true  => format!("{:20} is invalid - {}", pin, reason),
-                   false => format!("{:20} is invalid", pin),
-               }
-           }
-       }
-   );
+/// Write every valid pin in a text as file:line:column:pin
+///
+/// # Returns
+/// The number of pins that were found
+fn scan_text(name: &str, text: &[u8], reference: NaiveDate) -> usize {
+    let found = scan::find(text, reference);
+
+    for found in &found {
+        let pin = String::from_utf8_lossy(&text[found.start..found.end]);
+        println!("{}:{}:{}:{}", name, found.line, found.column, pin);
+    }
+
+    found.len()
 }
 
 
-fn valid(pin: String, args: &Cli) {
-   if !args.valid { return; }
+/// Scan a file, or every file in a directory and its subdirectories
+///
+/// Files that can't be read are written to stderr and the rest are still scanned.
+///
+/// # Arguments
+/// * `path` The file or directory to scan
+/// * `found` Counter for the pins that were found
+/// * `reference` The date pins are interpreted at
+///
+/// # Returns
+/// false if any file couldn't be read
+fn scan_path(path: &Path, found: &mut usize, reference: NaiveDate) -> bool {
+
+    if path.is_dir() {
+        let entries = fs::read_dir(path).and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<PathBuf>>>()
+        });
 
-  match args.porcelain {
-       true  => {
-           match args.reason {
-               true => println!("Y | {:25} | {}", "", pin),
-               false => println!("Y {}", pin),
+        let mut entries = match entries {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                return false
+            }
+        };
+        entries.sort();
+
+        // every entry is scanned even if an earlier one failed
+        let failed = entries.iter().filter(|entry| !scan_path(entry, found, reference)).count();
+        return failed == 0
+    }
 
-           }
-       },
-       false => println!("{:20} is valid", pin),
-   };
+    // files that aren't utf-8 are still searched, such as logs with mixed encodings
+    match fs::read(path) {
+        Ok(bytes) => {
+            *found += scan_text(&path.display().to_string(), &bytes, reference);
+            true
+        },
+        Err(err) => {
+            eprintln!("{}: {}", path.display(), err);
+            false
+        }
+    }
 }
 
 
+/// Scan the given paths or stdin
+///
+/// # Arguments
+/// * `paths` Files and directories to scan, empty to use stdin
+/// * `found` Counter for the pins that were found
+/// * `reference` The date pins are interpreted at
+///
+/// # Returns
+/// false if anything couldn't be read
+fn scan(paths: &[PathBuf], found: &mut usize, reference: NaiveDate) -> bool {
 
-fn main() {
-    let args = Cli::parse();
+    if paths.is_empty() {
+        let mut bytes = Vec::new();
+
+        return match io::stdin().read_to_end(&mut bytes) {
+            Err(err) => {
+                eprintln!("{}", err);
+                false
+            },
+            Ok(_) => {
+                *found += scan_text("-", &bytes, reference);
+                true
+            },
+        }
+    }
+
+    let failed = paths.iter().filter(|path| !scan_path(path, found, reference)).count();
+    failed == 0
+}
+
+
+
+fn redact(path: &Option<PathBuf>, style: Style, reference: NaiveDate) -> io::Result<()> {
+    let bytes = match path {
+        Some(path) => fs::read(path)?,
+        None => {
+            let mut bytes = Vec::new();
+            io::stdin().read_to_end(&mut bytes)?;
+            bytes
+        }
+    };
+
+    io::stdout().write_all(&scan::redact(&bytes, reference, style))
+}
+
+
+
+/// Write the control digit and the completed pin for each partial pin
+///
+/// The completed pin is written the same way as the partial one unless --format is given.
+/// Partial pins that can't be completed are written to stderr with the reason.
+///
+/// # Returns
+/// If every partial pin could be completed, or Err if stdin couldn't be read
+fn checkdigit(input: &Option<String>, format: Option<Format>, reference: NaiveDate) -> io::Result<bool> {
+
+    let text = match input {
+        Some(input) => input.clone(),
+        None => io::read_to_string(io::stdin())?,
+    };
+
+    let mut completed_all = true;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() { continue; }
+
+        match check::complete(line, reference) {
+            Ok(pin) => {
+                let completed = match format {
+                    Some(format) => pin.format(format, reference),
+                    None => format!("{}{}", line, pin.nums[9]),
+                };
+                println!("{} {}", pin.nums[9], completed);
+            },
+            Err(reason) => {
+                eprintln!("{} - {}", line, reason);
+                completed_all = false;
+            },
+        }
+    }
+
+    Ok(completed_all)
+}
+
+
+
+/// Write a fake pin for each line, in the same format as the real one
+///
+/// Lines that aren't valid pins are written as empty lines so the output lines up with the
+/// input, and the reason is written to stderr.
+///
+/// # Returns
+/// If every line was a valid pin, or Err if the key or the pins couldn't be read
+fn pseudonymize(key_file: &Path, path: &Option<PathBuf>, reference: NaiveDate) -> io::Result<bool> {
+
+    let key = fs::read(key_file)?;
+    let key = key.trim_ascii();
+
+    if key.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Key file is empty"))
+    }
 
-    if args.input.is_some() {
-        let input = args.input.clone().unwrap();
-        match check_pin(input.clone()) {
-            Ok(()) => {
-                valid(input, &args)
+    let text = match path {
+        Some(path) => fs::read_to_string(path)?,
+        None => io::read_to_string(io::stdin())?,
+    };
+
+    let mut all_valid = true;
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+
+        let fake = input::parse(line, reference)
+            .and_then(|pin| pin.validate(reference).map(|_| pin))
+            .and_then(|pin| pseudonym::pseudonymize(pin, key, reference));
+
+        match fake {
+            Ok(fake) => {
+                let format = Format::detect(line).unwrap_or(Format::Long);
+                println!("{}", fake.format(format, reference));
             },
             Err(reason) => {
-                invalid(input, reason, &args)
+                eprintln!("line {}: {} - {}", i + 1, line, reason);
+                println!();
+                all_valid = false;
             }
         }
-        return;
     }
 
-    let stdin = io::stdin();
-    let mut valid_count = 0;
-    let mut invalid_count = 0;
+    Ok(all_valid)
+}
+
+
+
+/// Check the numbers given as arguments, in files and on stdin and write the results
+///
+/// # Arguments
+/// * `args` The parsed command line
+/// * `reference` The date the numbers are interpreted at
+/// * `stdin` Read one number per line when there are no inputs or files
+/// * `out` Where the results are written
+///
+/// # Returns
+/// The exit code, see [INVALID] and [FAILED]
+fn check_all(args: &Cli, reference: NaiveDate, mut stdin: impl io::BufRead, out: impl Write) -> ExitCode {
+    let mut report = Report::new(args, reference, out);
+    let stop = |report: &Report| args.fail_fast && report.invalid > 0;
+
+    for input in &args.inputs {
+        check_and_report(&mut report, args, input, reference);
+        if stop(&report) { break; }
+    }
 
-    loop {
-        let mut buffer = String::new();
+    let mut failed = false;
 
-        match stdin.read_line(&mut buffer) {
-            Err(msg) => panic!("{}", msg),
-            Ok(0) => {
-                if args.count{
-                    println!(
-                        "{} valid, {} invalid, {} total",
-                        valid_count, invalid_count, valid_count+invalid_count);
+    if !stop(&report) {
+        for pattern in &args.files {
+            let paths = match files(pattern) {
+                Ok(paths) => paths,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    failed = true;
+                    continue;
                 }
-                break;
-            },
-            Ok(_) => {
-                match check_pin(buffer.trim().to_string()) {
-                    Ok(()) => {
-                        valid_count += 1;
-                        valid(buffer.trim().to_string(), &args)
-                    },
-                    Err(reason) => {
-                        invalid_count += 1;
-                        invalid(buffer.trim().to_string(), reason, &args)
+            };
+
+            for path in paths {
+                let (valid, invalid, filtered) = (report.valid, report.invalid, report.filtered);
+
+                let text = match fs::read_to_string(&path) {
+                    Ok(text) => text,
+                    Err(err) => {
+                        eprintln!("{}: {}", path.display(), err);
+                        failed = true;
+                        continue;
                     }
+                };
+
+                for line in text.lines() {
+                    check_and_report(&mut report, args, line.trim(), reference);
+                    if stop(&report) { break; }
                 }
+
+                report.file_totals(
+                    &path.display().to_string(),
+                    report.valid - valid,
+                    report.invalid - invalid,
+                    report.filtered - filtered);
+
+                if stop(&report) { break; }
+            }
+
+            if stop(&report) { break; }
+        }
+    }
+
+    if args.inputs.is_empty() && args.files.is_empty() {
+        loop {
+            let mut buffer = String::new();
+
+            match stdin.read_line(&mut buffer) {
+                Err(err) => {
+                    eprintln!("{}", err);
+                    failed = true;
+                    break;
+                },
+                Ok(0) => break,
+                Ok(_) => check_and_report(&mut report, args, buffer.trim(), reference),
             }
+
+            if stop(&report) { break; }
+        }
+    }
+
+    // a single number given as an argument doesn't need any totals
+    report.finish(args.inputs.len() != 1 || !args.files.is_empty());
+
+    // files that couldn't be read fail the run even if every number in the rest was valid
+    match failed {
+        true => ExitCode::from(FAILED),
+        false => exit_code(Ok::<bool, Error>(report.invalid == 0)),
+    }
+}
+
+
+
+
+/// Turn the result of a command into an exit code, writing the error if there is one
+///
+/// # Arguments
+/// * `result` If everything checked was valid, or the error that stopped the command
+fn exit_code(result: Result<bool, impl Display>) -> ExitCode {
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(INVALID),
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(FAILED)
+        }
+    }
+}
+
+
+
+fn main() -> ExitCode {
+    let args = Cli::parse();
+    let reference = args.reference_date.unwrap_or_else(utils::today);
+
+    if let Some(Command::Generate { date, from, to, count, long }) = args.command {
+        let format = match long {
+            true => Format::LongCompact,
+            false => Format::Short,
+        };
+
+        // generating only fails because of bad arguments
+        let result = generate(date, from, to, count, args.format.unwrap_or(format), reference);
+        return exit_code(result.map(|_| true));
+    }
+
+    if let Some(Command::Scan { paths }) = &args.command {
+        let mut found = 0;
+
+        // finding a pin is a failure when scanning for leaks, like an invalid number is
+        return match (scan(paths, &mut found, reference), found) {
+            (false, _) => ExitCode::from(FAILED),
+            (true, 0) => ExitCode::SUCCESS,
+            (true, _) => ExitCode::from(INVALID),
         }
     }
 
+    if let Some(Command::Checkdigit { input }) = &args.command {
+        return exit_code(checkdigit(input, args.format, reference));
+    }
+
+    if let Some(Command::Pseudonymize { key_file, path }) = &args.command {
+        return exit_code(pseudonymize(key_file, path, reference));
+    }
+
+    if let Some(Command::Redact { path, style }) = &args.command {
+        return exit_code(redact(path, *style, reference).map(|_| true));
+    }
+
+    if args.csv {
+        return exit_code(table::run(&args, reference, io::stdin(), io::stdout()));
+    }
+
+    check_all(&args, reference, io::stdin().lock(), io::stdout())
 }
 
 
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Fixed date to interpret numbers at, same as the library tests
+    fn reference() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 12, 1).unwrap()
+    }
+
+    /// Check a number with the given command line options, the way each input of `pin` is
+    fn check(options: &[&str], pin: &str) -> Result<Identity, Error> {
+        let args = Cli::try_parse_from(["pin"].iter().chain(options)).expect("Options should parse");
+        check_pin(pin.to_string(), &args, reference())
+    }
+
+    /// Run the checks with the given command line options and stdin, like `pin` would
+    ///
+    /// # Returns
+    /// The exit code and everything written to stdout
+    fn run(options: &[&str], stdin: &str) -> (ExitCode, String) {
+        let args = Cli::try_parse_from(["pin"].iter().chain(options)).expect("Options should parse");
+        let mut out = Vec::new();
+        let code = check_all(&args, reference(), stdin.as_bytes(), &mut out);
+        (code, String::from_utf8(out).expect("Output should be utf-8"))
+    }
+
+
+
+    #[test]
+    fn norwegian() {
+        //! --country no checks fødselsnummer and --country auto picks them for 11 digits
+
+        let parsed = check(&["--country", "no"], "31129949980");
+        assert!(matches!(parsed, Ok(Identity::Norwegian(_))));
+        assert_eq!(parsed.unwrap().country(), utils::Country::No);
+
+        assert!(matches!(check(&["--country", "auto"], "31129949980"), Ok(Identity::Norwegian(_))));
+        assert!(matches!(check(&["--country", "auto"], "850312-1231"), Ok(Identity::Person(_))));
+
+        // swedish is the default, where 11 digits are too short for the long form
+        assert!(matches!(check(&[], "31129949980"), Err(Error::InvalidSeparator { .. })));
+        assert_eq!(
+            check(&["--country", "no"], "31129949981").err(),
+            Some(Error::Modulus { modulus: 11, position: 11, expected: '0', found: '1' }),
+        );
+    }
+
+
+
+    #[test]
+    fn danish() {
+        //! --country dk checks CPR-nummer, with the modulus 11 check only when asked for, and
+        //! auto never picks them since they look like swedish pins
+
+        let parsed = check(&["--country", "dk"], "010190-1239");
+        assert!(matches!(parsed, Ok(Identity::Danish(_))));
+        assert_eq!(parsed.unwrap().country(), utils::Country::Dk);
+
+        assert_eq!(
+            check(&["--country", "dk", "--cpr-modulus"], "010190-1239").err(),
+            Some(Error::Modulus { modulus: 11, position: 10, expected: '8', found: '9' }),
+        );
+        assert!(matches!(check(&["--country", "dk", "--cpr-modulus"], "010190-1238"), Ok(Identity::Danish(_))));
+
+        // read as a swedish samordningsnummer, which fails luhns
+        assert!(matches!(check(&["--country", "auto"], "010190-1238"), Err(Error::Checksum { .. })));
+    }
+
+
+
+    #[test]
+    fn finnish() {
+        //! --country fi checks henkilötunnus and auto picks them when a letter shows they
+        //! can't be swedish
+
+        let parsed = check(&["--country", "fi"], "311299+0029");
+        assert!(matches!(parsed, Ok(Identity::Finnish(_))));
+        assert_eq!(parsed.unwrap().country(), utils::Country::Fi);
+
+        for hetu in ["010594Y9021", "131052-308T"] {
+            assert!(matches!(check(&["--country", "auto"], hetu), Ok(Identity::Finnish(_))), "{}", hetu);
+        }
+
+        // a digit control char and a - or + is read as a swedish pin
+        assert!(matches!(check(&["--country", "auto"], "311299+0029"), Err(Error::DayOutOfRange { .. })));
+        assert_eq!(
+            check(&["--country", "fi"], "131052-308A").err(),
+            Some(Error::Modulus { modulus: 31, position: 11, expected: 'T', found: 'A' }),
+        );
+    }
+
+
+
+    #[test]
+    fn json() {
+        //! --output json writes one well formed array with an object per number and the summary
+        //! last, even for a single number
+
+        let fields = [
+            "type", "input", "cleaned", "country", "valid", "error", "message", "normalized",
+            "birth_date", "kind", "group", "gender", "changes",
+        ];
+
+        let (_, out) = run(&["--output", "json", "8501010014", "12"], "");
+        let value: serde_json::Value = serde_json::from_str(&out).expect("Output should be json");
+        let objects = value.as_array().expect("Output should be an array");
+
+        assert_eq!(objects.len(), 3);
+        for object in &objects[..2] {
+            let keys = object.as_object().unwrap().keys().map(|key| key.as_str()).collect::<Vec<&str>>();
+            assert_eq!(keys, fields);
+        }
+
+        assert_eq!(objects[0]["valid"], true);
+        assert_eq!(objects[0]["normalized"], "19850101-0014");
+        assert_eq!(objects[0]["country"], "se");
+        assert_eq!(objects[1]["valid"], false);
+        assert_eq!(objects[1]["error"], "length");
+        assert_eq!(objects[2]["type"], "summary");
+        assert_eq!(objects[2]["total"], 2);
+
+        let (_, out) = run(&["--output", "json", "8501010014"], "");
+        let value: serde_json::Value = serde_json::from_str(&out).expect("Output should be json");
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        assert_eq!(value[1]["type"], "summary");
+
+        // nothing to check still gives an array
+        let (_, out) = run(&["--output", "json", "--count"], "");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&out).unwrap(), serde_json::json!([]));
+    }
+
+
+
+    #[test]
+    fn jsonl() {
+        //! --output jsonl writes one object per line with the summary on the last line
+
+        let (_, out) = run(&["--output", "jsonl"], "8501010014\n 85 01 01-0014\n");
+        let lines = out.lines()
+            .map(|line| serde_json::from_str(line).expect("Each line should be json"))
+            .collect::<Vec<serde_json::Value>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["changes"], serde_json::json!([]));
+        assert_eq!(lines[1]["changes"], serde_json::json!(["whitespace"]));
+        assert_eq!(lines[1]["input"], "85 01 01-0014");
+        assert_eq!(lines[1]["cleaned"], "850101-0014");
+        assert_eq!(lines[2]["type"], "summary");
+        assert_eq!(lines[2]["valid"], 2);
+
+        let (_, out) = run(&["--output", "jsonl", "8501010014"], "");
+        assert_eq!(out.lines().count(), 2);
+    }
+
+
+
+    /// Run --csv with the given command line options and csv
+    ///
+    /// # Returns
+    /// If every number was valid and the csv that was written
+    fn csv(options: &[&str], input: &str) -> (bool, String) {
+        let args = Cli::try_parse_from(["pin", "--csv"].iter().chain(options)).expect("Options should parse");
+        let mut out = Vec::new();
+        let valid = table::run(&args, reference(), input.as_bytes(), &mut out).expect("Csv should be checked");
+        (valid, String::from_utf8(out).expect("Output should be utf-8"))
+    }
+
+
+
+    #[test]
+    fn csv_columns() {
+        //! The column is found by name or position, and results are appended to every row
+
+        let input = "name,ssn\nAnna,8501010014\nBo,12\n";
+        let expected = "\
+            name,ssn,pin_valid,pin_reason,pin_normalized,pin_birthdate\n\
+            Anna,8501010014,true,,19850101-0014,1985-01-01\n\
+            Bo,12,false,length,,\n";
+
+        assert_eq!(csv(&["--column", "ssn"], input), (false, expected.to_string()));
+        assert_eq!(csv(&["--column", "2"], input), (false, expected.to_string()));
+
+        let args = Cli::try_parse_from(["pin", "--csv", "--column", "id"]).unwrap();
+        assert!(table::run(&args, reference(), input.as_bytes(), Vec::new()).is_err());
+
+        let args = Cli::try_parse_from(["pin", "--csv", "--column", "0"]).unwrap();
+        assert!(table::run(&args, reference(), input.as_bytes(), Vec::new()).is_err());
+    }
+
+
+
+    #[test]
+    fn csv_rows() {
+        //! Without a header the first row is checked too, short rows are padded so the results
+        //! line up and --fail-fast stops at the first invalid number
+
+        let (valid, out) = csv(&["--no-header", "--column", "2", "--delimiter", ";"], "Anna;8501010014;x\nBo\n");
+        assert!(!valid);
+        assert_eq!(out, "\
+            Anna;8501010014;x;true;;19850101-0014;1985-01-01\n\
+            Bo;;false;length;;\n");
+
+        let (valid, out) = csv(&["--column", "ssn"], "ssn\n8501010014\n");
+        assert!(valid);
+        assert_eq!(out.lines().count(), 2);
+
+        let (_, out) = csv(&["--column", "ssn", "--fail-fast"], "ssn\n12\n8501010014\n");
+        assert_eq!(out, "ssn,pin_valid,pin_reason,pin_normalized,pin_birthdate\n12,false,length,,\n");
+    }
+
+
+
+    #[test]
+    fn exit_codes() {
+        //! 0 when every number is valid, 1 when any is invalid and 2 when the command failed
+
+        assert_eq!(exit_code(Ok::<bool, Error>(true)), ExitCode::SUCCESS);
+        assert_eq!(exit_code(Ok::<bool, Error>(false)), ExitCode::from(INVALID));
+        assert_eq!(exit_code(Err::<bool, Error>(Error::ReversedRange)), ExitCode::from(FAILED));
+
+        assert_eq!(run(&["8501010014"], "").0, ExitCode::SUCCESS);
+        assert_eq!(run(&["8501010014", "12"], "").0, ExitCode::from(INVALID));
+        assert_eq!(run(&[], "8501010014\n12\n").0, ExitCode::from(INVALID));
+    }
+
+
+
+    #[test]
+    fn fail_fast() {
+        //! --fail-fast stops at the first invalid number, both for inputs and stdin
+
+        let (code, out) = run(&["--fail-fast", "12", "8501010014"], "");
+        assert_eq!(code, ExitCode::from(INVALID));
+        assert_eq!(out, "12                   is invalid - Too short, 2 chars\n0 valid, 1 invalid, 1 total\n");
+
+        let (_, out) = run(&["--fail-fast", "--count"], "8501010014\n12\n13\n8501010014\n");
+        assert_eq!(out.lines().count(), 2);
+
+        // without it everything is checked
+        let (_, out) = run(&[], "12\n8501010014\n");
+        assert!(out.ends_with("1 valid, 1 invalid, 2 total\n"));
+    }
+
+
+
+    /// Create an empty directory for a test with the given files in it
+    fn directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pin-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Temp dir should be created");
+
+        for (file, text) in files {
+            fs::write(dir.join(file), text).expect("Temp file should be written");
+        }
+
+        dir
+    }
+
+
+
+    #[test]
+    fn several_inputs() {
+        //! every positional input is checked in order and followed by the totals
+
+        let (code, out) = run(&["8501010014", "12", "850312-1231"], "");
+        assert_eq!(code, ExitCode::from(INVALID));
+        assert_eq!(out, "\
+            8501010014           is valid - personnummer\n\
+            12                   is invalid - Too short, 2 chars\n\
+            850312-1231          is valid - personnummer\n\
+            2 valid, 1 invalid, 3 total\n");
+    }
+
+
+
+    #[test]
+    fn file_globs() {
+        //! globs are expanded, each file gets its own totals and the totals of everything come
+        //! last
+
+        let dir = directory("globs", &[
+            ("a.txt", "8501010014\n12\n"),
+            ("b.txt", "850312-1231\n"),
+            ("c.csv", "12\n"),
+        ]);
+        let pattern = dir.join("*.txt").display().to_string();
+
+        let (code, out) = run(&["--file", &pattern], "");
+        assert_eq!(code, ExitCode::from(INVALID));
+
+        let lines = out.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2], format!("{}: 1 valid, 1 invalid, 2 total", dir.join("a.txt").display()));
+        assert_eq!(lines[4], format!("{}: 1 valid, 0 invalid, 1 total", dir.join("b.txt").display()));
+        assert_eq!(lines[5], "2 valid, 1 invalid, 3 total");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+
+
+    #[test]
+    fn missing_files() {
+        //! files that can't be found or read fail the run, but the rest are still checked
+
+        let dir = directory("missing", &[("a.txt", "8501010014\n")]);
+        fs::create_dir(dir.join("b.txt")).unwrap();
+
+        let missing = dir.join("missing.txt").display().to_string();
+        let folder = dir.join("b.txt").display().to_string();
+        let found = dir.join("a.txt").display().to_string();
+
+        let (code, out) = run(&["-f", &missing, "-f", &folder, "-f", &found], "");
+        assert_eq!(code, ExitCode::from(FAILED));
+        assert!(out.contains(&format!("{}: 1 valid, 0 invalid, 1 total", found)));
+        assert!(out.ends_with("1 valid, 0 invalid, 1 total\n"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+
+
+    #[test]
+    fn typed_input() {
+        //! results show the number as it was typed along with what was cleaned up, except for
+        //! porcelain which needs numbers without whitespace
+
+        let (_, out) = run(&["85 03 12–1231", "85 03 12–1232"], "");
+        assert_eq!(out, "\
+            85 03 12–1231        is valid - personnummer (removed whitespace, replaced unicode dashes)\n\
+            85 03 12–1232        is invalid - Luhns, control digit is 2 but should be 1 (removed whitespace, replaced unicode dashes)\n\
+            1 valid, 1 invalid, 2 total\n");
+
+        let (_, out) = run(&["--porcelain", "--reason", "85 03 12–1231"], "");
+        assert_eq!(out, "Y 850312-1231 personnummer male\n");
+    }
+}
@@ -0,0 +1,260 @@
+use chrono::NaiveDate;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::check;
+use crate::error::Error;
+use crate::utils::{ Date, Gender };
+
+
+
+/// Weights for the first control digit, used on the first 9 digits
+const FIRST: [u8; 9] = [3, 7, 6, 1, 8, 9, 4, 5, 2];
+
+/// Weights for the second control digit, used on the first 10 digits
+const SECOND: [u8; 10] = [5, 4, 3, 2, 7, 6, 5, 4, 3, 2];
+
+
+
+
+/// The kind of number a fødselsnummer is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Regular norwegian identity number
+    Fodselsnummer,
+
+    /// Given to people who aren't registered as living in norway. The day of the number is
+    /// increased by 40
+    DNummer,
+
+    /// Temporary number used by health services. The month of the number is increased by 40
+    HNummer,
+}
+
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Kind::Fodselsnummer => "fødselsnummer",
+            Kind::DNummer => "d-nummer",
+            Kind::HNummer => "h-nummer",
+        })
+    }
+}
+
+
+
+
+/// A parsed norwegian fødselsnummer, DDMMYYIIICC
+#[derive(Debug, Clone, Copy)]
+pub struct Fodselsnummer {
+    pub nums: [u8; 11],
+    pub date: Date,
+    pub kind: Kind,
+}
+
+
+impl Fodselsnummer {
+    /// Run all checks on the number at the given date, see [check()]
+    pub fn validate(&self, reference: NaiveDate) -> Result<(), Error> {
+        check(*self, reference)
+    }
+
+    /// Get the legal gender from the 9th digit, odd for male and even for female
+    pub fn gender(&self) -> Gender {
+        match self.nums[8] % 2 {
+            0 => Gender::Female,
+            _ => Gender::Male,
+        }
+    }
+}
+
+
+impl fmt::Display for Fodselsnummer {
+    /// Writes the number as its 11 digits, there is no other format
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.nums.iter().map(|n| n.to_string()).collect::<String>())
+    }
+}
+
+
+impl FromStr for Fodselsnummer {
+    type Err = Error;
+
+    /// Parse a fødselsnummer, see [parse]
+    fn from_str(s: &str) -> Result<Fodselsnummer, Self::Err> {
+        parse(s)
+    }
+}
+
+
+impl TryFrom<&str> for Fodselsnummer {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Fodselsnummer, Self::Error> {
+        value.parse()
+    }
+}
+
+
+
+
+/// Find the centry from the individual number
+///
+/// The individual numbers are given out in ranges, where each range is used for a different
+/// span of years.
+///
+/// # Arguments
+/// * `individual` The 7th to 9th digits
+/// * `year` The 5th and 6th digits
+///
+/// # Returns
+/// The centry, for example `19`, or None if the individual number isn't used for that year
+fn get_centry(individual: u16, year: u8) -> Option<i32> {
+    match (individual, year) {
+        (0..=499, _) => Some(19),
+        (500..=749, 54..=99) => Some(18),
+        (500..=999, 0..=39) => Some(20),
+        (900..=999, 40..=99) => Some(19),
+        _ => None,
+    }
+}
+
+
+
+
+/// Calculate the two control digits
+///
+/// # Arguments
+/// * `nums` The first 9 digits
+///
+/// # Returns
+/// The 10th and 11th digits, or None if no control digit makes the number valid
+pub fn control_digits(nums: [u8; 9]) -> Option<(u8, u8)> {
+
+    let digit = |sum: u32| match 11 - sum % 11 {
+        11 => Some(0),
+        10 => None,
+        digit => Some(digit as u8),
+    };
+
+    let sum = nums.iter().zip(FIRST).map(|(num, weight)| (num * weight) as u32).sum::<u32>();
+    let first = digit(sum)?;
+
+    let sum = nums.iter().chain([first].iter())
+        .zip(SECOND)
+        .map(|(num, weight)| (num * weight) as u32)
+        .sum::<u32>();
+    let second = digit(sum)?;
+
+    Some((first, second))
+}
+
+
+
+
+/// Parse a norwegian fødselsnummer
+///
+/// The number is 11 digits without any separator. D-numbers have 40 added to the day and
+/// H-numbers have 40 added to the month, it is removed from the birth date.
+///
+/// # Arguments
+/// * `input` The input string to parse
+///
+/// # Returns
+/// * `Result<Fodselsnummer, Error>` The parsed number or the reason it is invalid
+pub fn parse(input: &str) -> Result<Fodselsnummer, Error> {
+    let chars = input.chars().collect::<Vec<char>>();
+
+    if chars.len() != 11 {
+        return Err(Error::InvalidLength { length: chars.len(), min: 11, max: 11 })
+    }
+
+    let mut nums = [0; 11];
+    for (i, char) in chars.iter().enumerate() {
+        match char.to_digit(10) {
+            Some(digit) => nums[i] = digit as u8,
+            None => return Err(Error::NotDigit { position: i + 1, found: *char }),
+        }
+    }
+
+    let mut day = (nums[0] * 10 + nums[1]) as u32;
+    let mut month = (nums[2] * 10 + nums[3]) as u32;
+    let year = nums[4] * 10 + nums[5];
+    let individual = nums[6] as u16 * 100 + nums[7] as u16 * 10 + nums[8] as u16;
+
+    let kind = match (day > 40, month > 40) {
+        (true, _) => Kind::DNummer,
+        (false, true) => Kind::HNummer,
+        _ => Kind::Fodselsnummer,
+    };
+
+    match kind {
+        Kind::DNummer => day -= 40,
+        Kind::HNummer => month -= 40,
+        Kind::Fodselsnummer => {},
+    }
+
+    let centry = get_centry(individual, year)
+        .ok_or(Error::IndividualNumber { individual, year })?;
+
+    Ok(Fodselsnummer {
+        nums,
+        date: Date {
+            day,
+            month,
+            year: centry * 100 + year as i32,
+        },
+        kind,
+    })
+}
+
+
+
+
+/// Does all the checks on a fødselsnummer
+///
+/// The birth date has to exist and can't be after the reference date, and both control
+/// digits have to match.
+///
+/// # Arguments
+/// * `number` The parsed number
+/// * `reference` The date the number is interpreted at
+///
+/// # Returns
+/// Ok with no value if the number passed all the tests.
+/// Err with the reason the first failing test failed.
+pub fn check(number: Fodselsnummer, reference: NaiveDate) -> Result<(), Error> {
+
+    check::birth_date(number.date, reference)?;
+
+    let mut first = [0; 9];
+    first.copy_from_slice(&number.nums[..9]);
+
+    let (expected, second) = control_digits(first).ok_or(Error::NoControlDigit)?;
+
+    for (i, expected) in [(9, expected), (10, second)] {
+        if number.nums[i] != expected {
+            return Err(Error::Modulus {
+                modulus: 11,
+                position: i + 1,
+                expected: char::from(b'0' + expected),
+                found: char::from(b'0' + number.nums[i]),
+            })
+        }
+    }
+
+    Ok(())
+}
+
+
+
+
+/// Check if an input looks like a fødselsnummer rather than a swedish number
+///
+/// Swedish numbers with 11 chars always have a separator, so 11 digits can only be
+/// norwegian.
+pub fn detect(input: &str) -> bool {
+    input.chars().count() == 11 && input.chars().all(|char| char.is_ascii_digit())
+}
+
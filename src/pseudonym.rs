@@ -0,0 +1,151 @@
+use chrono::prelude::*;
+use hmac::{ Hmac, KeyInit, Mac };
+use sha2::Sha256;
+
+use crate::check::{ birth_date, control_digit };
+use crate::error::Error;
+use crate::utils::{ Date, Kind, Pin };
+
+
+
+/// Number of feistel rounds used by [permute]
+const ROUNDS: u8 = 8;
+
+/// Serial numbers with the same gender, every other of the 1000 possible
+const SERIALS: u32 = 500;
+
+
+
+
+/// Keyed round function of the feistel network
+///
+/// # Arguments
+/// * `key` The secret key
+/// * `year` The birth year, so every year gets its own permutation
+/// * `round` Which round this is
+/// * `half` The half of the value to hash
+/// * `size` The size of each half
+///
+/// # Returns
+/// A number from 0 to `size`
+fn round(key: &[u8], year: i32, round: u8, half: u32, size: u32) -> u32 {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any length");
+
+    mac.update(&year.to_be_bytes());
+    mac.update(&[round]);
+    mac.update(&half.to_be_bytes());
+
+    let hash = mac.finalize().into_bytes();
+    let mut first = [0;8];
+    first.copy_from_slice(&hash[..8]);
+
+    (u64::from_be_bytes(first) % size as u64) as u32
+}
+
+
+
+
+/// Keyed permutation of the numbers from 0 to `size*size`
+fn permute(key: &[u8], year: i32, value: u32, size: u32) -> u32 {
+    let mut left = value / size;
+    let mut right = value % size;
+
+    for i in 0..ROUNDS {
+        let next = (left + round(key, year, i, right, size)) % size;
+        left = right;
+        right = next;
+    }
+
+    left * size + right
+}
+
+
+
+
+/// Map a pin to a fake but valid pin using a secret key
+///
+/// The same pin and key always give the same fake pin, and two different pins never give the
+/// same fake pin. The fake pin keeps the birth year, the gender and the kind of the real one,
+/// while the birth day and the serial number are replaced. The control digit is recalculated
+/// with [control_digit].
+///
+/// People born the same year as `reference` are only mapped to birth dates up to `reference`,
+/// which means their fake pins can change as time passes.
+///
+/// # Arguments
+/// * `pin` A pin that has passed [crate::check::full]
+/// * `key` The secret key
+/// * `reference` The date the pin is interpreted at
+///
+/// # Returns
+/// * `Result<Pin, Error>` The fake pin, or Err if the birth date doesn't exist or is after
+///   `reference`
+pub fn pseudonymize(pin: Pin, key: &[u8], reference: NaiveDate) -> Result<Pin, Error> {
+
+    birth_date(pin.date, reference)?;
+    let born = pin.date.naive().expect("Birth date was checked");
+
+    let year = pin.date.year;
+    let days = NaiveDate::from_ymd_opt(year, 12, 31).unwrap().ordinal();
+
+    // only days up to the reference date can be used for people born this year
+    let allowed = match reference.year() == year {
+        true => reference.ordinal(),
+        false => days,
+    };
+
+    // the gender is kept by only changing the serial to others of the same parity
+    let serial = pin.nums[6] as u32 * 100 + pin.nums[7] as u32 * 10 + pin.nums[8] as u32;
+    let parity = serial % 2;
+
+    let value = born.ordinal0() * SERIALS + (serial / 10) * 5 + (serial % 10) / 2;
+
+    // smallest square that fits every value of the year
+    let mut size = 1;
+    while size * size < days * SERIALS {
+        size += 1;
+    }
+
+    // walk the cycle until it lands on an allowed value, this keeps it a permutation
+    let mut value = permute(key, year, value, size);
+    while value >= allowed * SERIALS {
+        value = permute(key, year, value, size);
+    }
+
+    let date = NaiveDate::from_yo_opt(year, value / SERIALS + 1).unwrap();
+    let index = value % SERIALS;
+    let serial = (index / 5) * 10 + (index % 5) * 2 + parity;
+
+    let day = match pin.kind {
+        Kind::Samordningsnummer => date.day() + 60,
+        Kind::Personnummer => date.day(),
+    };
+    let decade = year.rem_euclid(100) as u32;
+
+    let digits = [
+        decade / 10, decade % 10,
+        date.month() / 10, date.month() % 10,
+        day / 10, day % 10,
+        serial / 100, serial / 10 % 10, serial % 10,
+    ];
+
+    let mut first = [0;9];
+    for (i, digit) in digits.iter().enumerate() {
+        first[i] = *digit as u8;
+    }
+
+    let mut nums = [0;10];
+    nums[..9].copy_from_slice(&first);
+    nums[9] = control_digit(first);
+
+    Ok(Pin {
+        nums,
+        plus: pin.plus,
+        date: Date {
+            year,
+            month: date.month(),
+            day: date.day(),
+        },
+        kind: pin.kind,
+    })
+}
@@ -0,0 +1,360 @@
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde_json::{ json, Value };
+use std::fmt::Display;
+use std::io::Write;
+
+use pin::{ explain, suggest, Country, Error, Format, Identity };
+use pin::input::Change;
+
+use crate::{ country, Cli, Mode };
+
+
+
+/// Most suggestions written for a single invalid number
+const SUGGESTIONS: usize = 5;
+
+/// Width of the kind column of porcelain output, fits the longest label which is
+/// "organisationsnummer economic-association"
+const KIND_WIDTH: usize = 40;
+
+
+
+
+/// How results should be written
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// Text meant for people, or for scripts with --porcelain
+    Text,
+
+    /// A single json array with one object per number and a summary at the end
+    Json,
+
+    /// One json object per line and a summary on the last line
+    Jsonl,
+}
+
+
+
+
+/// Writes the result for each checked number and keeps count of them
+pub struct Report<'a> {
+    args: &'a Cli,
+    reference: NaiveDate,
+    out: Box<dyn Write + 'a>,
+    pub valid: u32,
+    pub invalid: u32,
+
+    /// valid numbers that were hidden by --gender or the age and birth date filters
+    pub filtered: u32,
+
+    /// number of json objects written, used to know where commas are needed
+    written: u32,
+}
+
+
+impl<'a> Report<'a> {
+    pub fn new(args: &'a Cli, reference: NaiveDate, out: impl Write + 'a) -> Report<'a> {
+        let mut report = Report {
+            args,
+            reference,
+            out: Box::new(out),
+            valid: 0,
+            invalid: 0,
+            filtered: 0,
+            written: 0,
+        };
+
+        if args.output == Output::Json {
+            report.line("[");
+        }
+
+        report
+    }
+
+
+    /// Write a line to the output the report was made with
+    fn line(&mut self, line: impl Display) {
+        writeln!(self.out, "{}", line).expect("Failed to write output");
+    }
+
+
+    /// Write a json object, as part of the array or on its own line
+    fn json(&mut self, value: Value) {
+        if self.args.output == Output::Json && self.written > 0 {
+            self.line(",");
+        }
+
+        match self.args.output {
+            Output::Json => write!(self.out, "  {}", value).expect("Failed to write output"),
+            _ => self.line(value),
+        }
+
+        self.written += 1;
+    }
+
+
+    /// Valid pins the number might have been meant to be, empty unless asked for
+    fn suggestions(&self, pin: &str) -> Vec<String> {
+        if !self.args.suggest || self.args.mode == Mode::Org || country(pin, self.args) != Country::Se {
+            return Vec::new();
+        }
+
+        suggest::suggest(pin, self.reference)
+            .into_iter()
+            .take(SUGGESTIONS)
+            .map(|suggestion| suggestion.text)
+            .collect()
+    }
+
+
+    /// Write how the number was checked, if asked for
+    fn explain(&mut self, pin: &str) {
+        if !self.args.explain || self.args.mode == Mode::Org || country(pin, self.args) != Country::Se {
+            return;
+        }
+
+        for line in explain::explain(pin, self.reference) {
+            self.line(format!("    {}", line));
+        }
+    }
+
+
+    /// Note for people about what was cleaned up in the number, empty if nothing was
+    fn changes(changes: &[Change]) -> String {
+        if changes.is_empty() {
+            return String::new();
+        }
+
+        let changes = changes.iter().map(|change| change.to_string()).collect::<Vec<String>>();
+        format!(" ({})", changes.join(", "))
+    }
+
+
+    /// Write a number that failed the checks
+    ///
+    /// # Arguments
+    /// * `input` The number as it was given
+    /// * `pin` The number after it was normalized
+    /// * `changes` What was changed when normalizing it
+    /// * `reason` Why it is invalid
+    pub fn invalid(&mut self, input: &str, pin: &str, changes: &[Change], reason: Error) {
+        self.invalid += 1;
+
+        let args = self.args;
+        if !args.invalid { return; }
+
+        let suggestions = self.suggestions(pin);
+
+        if args.output != Output::Text {
+            let mut value = json!({
+                "type": "result",
+                "input": input,
+                "cleaned": pin,
+                "country": country(pin, args).to_string(),
+                "valid": false,
+                "error": reason.code(),
+                "message": reason.to_string(),
+                "normalized": null,
+                "birth_date": null,
+                "kind": null,
+                "group": null,
+                "gender": null,
+                "changes": changes.iter().map(|change| change.code()).collect::<Vec<&str>>(),
+            });
+
+            if args.suggest {
+                value["suggestions"] = json!(suggestions);
+            }
+
+            self.json(value);
+            return;
+        }
+
+        let line = match args.porcelain {
+            true => {
+                match args.reason {
+                    // same columns as valid lines, invalid numbers have no gender
+                    true  => format!("! | {:^width$} | {:15} | -", reason.code(), pin, width = KIND_WIDTH),
+                    false => format!("! {}", pin),
+                }
+            },
+
+            false => {
+                match args.reason {
+                    true  => format!("{:20} is invalid - {}", input, reason),
+                    false => format!("{:20} is invalid", input),
+                }
+            }
+        };
+
+        let line = match args.porcelain {
+            true => line,
+            false => line + &Report::changes(changes),
+        };
+
+        match (suggestions.is_empty(), args.porcelain) {
+            (true, _) => self.line(line),
+            (false, true) => self.line(format!("{} | {}", line, suggestions.join(","))),
+            (false, false) => self.line(format!("{} (did you mean {}?)", line, suggestions.join(", "))),
+        }
+
+        self.explain(pin);
+    }
+
+
+    /// Write a number that passed the checks
+    ///
+    /// # Arguments
+    /// * `input` The number as it was given
+    /// * `pin` The number after it was normalized
+    /// * `changes` What was changed when normalizing it
+    /// * `parsed` The number
+    pub fn valid(&mut self, input: &str, pin: &str, changes: &[Change], parsed: Identity) {
+        self.valid += 1;
+
+        let args = self.args;
+        if !args.valid { return; }
+
+        if args.output != Output::Text {
+            let (kind, group) = match parsed {
+                Identity::Person(pin) => (pin.kind.to_string(), None),
+                Identity::Norwegian(number) => (number.kind.to_string(), None),
+                Identity::Danish(_) => ("cpr-nummer".to_string(), None),
+                Identity::Finnish(_) => ("henkilötunnus".to_string(), None),
+                Identity::Organisation(org) => (
+                    "organisationsnummer".to_string(),
+                    org.group.map(|group| group.to_string()),
+                ),
+            };
+
+            let format = args.format.unwrap_or(Format::Long);
+            let birth_date = parsed.date()
+                .and_then(|date| date.naive())
+                .map(|date| date.to_string());
+
+            self.json(json!({
+                "type": "result",
+                "input": input,
+                "cleaned": pin,
+                "country": parsed.country().to_string(),
+                "valid": true,
+                "error": null,
+                "message": null,
+                "normalized": parsed.format(format, self.reference),
+                "birth_date": birth_date,
+                "kind": kind,
+                "group": group,
+                "gender": parsed.gender().map(|gender| gender.to_string()),
+                "changes": changes.iter().map(|change| change.code()).collect::<Vec<&str>>(),
+            }));
+            return;
+        }
+
+        let cleaned = pin;
+
+        // porcelain columns are split on whitespace, so they get the cleaned number
+        let pin = match (args.format, args.porcelain) {
+            (Some(format), _) => parsed.format(format, self.reference),
+            (None, true) => cleaned.to_string(),
+            (None, false) => input.to_string(),
+        };
+
+        let gender = match parsed.gender() {
+            Some(gender) => gender.to_string(),
+            None => "-".to_string(),
+        };
+
+        let line = match args.porcelain {
+            true  => {
+                match args.reason {
+                    true => format!("Y | {:^width$} | {:15} | {}", parsed.kind_label(), pin, gender, width = KIND_WIDTH),
+                    false => format!("Y {} {} {}", pin, parsed.kind_label(), gender),
+                }
+            },
+            false => format!("{:20} is valid - {}{}", pin, parsed.kind_label(), Report::changes(changes)),
+        };
+
+        self.line(line);
+
+        self.explain(cleaned);
+    }
+
+
+    /// Count a valid number that was left out by the filters
+    pub fn filtered(&mut self) {
+        self.filtered += 1;
+    }
+
+
+    /// Write the totals of a single file if they aren't hidden
+    ///
+    /// # Arguments
+    /// * `name` The name of the file
+    /// * `valid` Number of valid numbers in the file
+    /// * `invalid` Number of invalid numbers in the file
+    /// * `filtered` Number of valid numbers in the file that were left out by the filters
+    pub fn file_totals(&mut self, name: &str, valid: u32, invalid: u32, filtered: u32) {
+        if !self.args.count { return; }
+
+        match self.args.output {
+            Output::Text => self.line(format!("{}: {}", name, summary(valid, invalid, filtered))),
+
+            _ => self.json(json!({
+                "type": "summary",
+                "file": name,
+                "valid": valid,
+                "invalid": invalid,
+                "filtered": filtered,
+                "total": valid + invalid + filtered,
+            })),
+        }
+    }
+
+
+    /// Write the totals if they aren't hidden and close the json array
+    ///
+    /// # Arguments
+    /// * `totals` If the text totals should be written, false when checking a single number.
+    ///   Json always ends with a summary object unless --count hides it
+    pub fn finish(&mut self, totals: bool) {
+        if (totals || self.args.output != Output::Text) && self.args.count {
+            match self.args.output {
+                Output::Text => self.line(summary(self.valid, self.invalid, self.filtered)),
+
+                _ => self.json(json!({
+                    "type": "summary",
+                    "valid": self.valid,
+                    "invalid": self.invalid,
+                    "filtered": self.filtered,
+                    "total": self.valid + self.invalid + self.filtered,
+                })),
+            }
+        }
+
+        if self.args.output == Output::Json {
+            if self.written > 0 {
+                self.line("");
+            }
+            self.line("]");
+        }
+    }
+}
+
+
+
+
+/// Write the totals as text, the filtered numbers are only mentioned when there are any
+///
+/// # Arguments
+/// * `valid` Number of valid numbers that were shown
+/// * `invalid` Number of invalid numbers
+/// * `filtered` Number of valid numbers that were left out by the filters
+pub fn summary(valid: u32, invalid: u32, filtered: u32) -> String {
+    match filtered {
+        0 => format!("{} valid, {} invalid, {} total", valid, invalid, valid + invalid),
+        _ => format!(
+            "{} valid, {} invalid, {} filtered, {} total",
+            valid, invalid, filtered, valid + invalid + filtered),
+    }
+}
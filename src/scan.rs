@@ -0,0 +1,222 @@
+use chrono::NaiveDate;
+use std::str::FromStr;
+
+use crate::{ check, input };
+use crate::utils::Pin;
+
+
+
+/// A valid pin found in a text
+#[derive(Debug, Clone, Copy)]
+pub struct Match {
+    /// Byte offset of the first char of the pin
+    pub start: usize,
+
+    /// Byte offset after the last char of the pin
+    pub end: usize,
+
+    /// Line the pin is on, counted from 1
+    pub line: usize,
+
+    /// Char the pin starts at on its line, counted from 1
+    pub column: usize,
+
+    pub pin: Pin,
+}
+
+
+
+
+/// A run of digits in a text
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start: usize,
+    end: usize,
+}
+
+
+impl Run {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+
+
+
+/// Find all runs of ascii digits in a text
+fn runs(text: &[u8]) -> Vec<Run> {
+    let mut runs = Vec::new();
+    let mut start = None;
+
+    for (i, byte) in text.iter().enumerate() {
+        match (byte.is_ascii_digit(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(begin)) => {
+                runs.push(Run { start: begin, end: i });
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    if let Some(begin) = start {
+        runs.push(Run { start: begin, end: text.len() });
+    }
+
+    runs
+}
+
+
+
+
+/// Find every valid pin in a text
+///
+/// Looks for the same shapes as [input::parse] accepts, surrounded by anything but digits.
+/// Every candidate is checked with [check::full] so random numbers that happen to have the
+/// right length are left out. The separator isn't checked, a pin written with the wrong one is
+/// still a pin.
+///
+/// The text is searched as bytes so it doesn't have to be valid utf-8.
+///
+/// # Arguments
+/// * `text` The text to search
+/// * `reference` The date pins are interpreted at, see [input::parse]
+///
+/// # Returns
+/// The valid pins in the order they appear
+pub fn find(text: &[u8], reference: NaiveDate) -> Vec<Match> {
+    let runs = runs(text);
+    let mut candidates = Vec::new();
+
+    let mut i = 0;
+    while i < runs.len() {
+        let run = runs[i];
+
+        // a run followed by a separator and 4 more digits
+        if let Some(next) = runs.get(i + 1) {
+            let separated = next.start == run.end + 1 && next.len() == 4;
+
+            let allowed = match run.len() {
+                6 | 8 => text[run.end] == b'-' || text[run.end] == b'+',
+                _ => false,
+            };
+
+            if separated && allowed {
+                candidates.push((run.start, next.end));
+                i += 2;
+                continue;
+            }
+        }
+
+        if run.len() == 10 || run.len() == 12 {
+            candidates.push((run.start, run.end));
+        }
+
+        i += 1;
+    }
+
+    let mut matches = Vec::new();
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut counted = 0;
+
+    for (start, end) in candidates {
+        // the candidate is only digits and a separator, so always valid utf-8
+        let candidate = String::from_utf8_lossy(&text[start..end]);
+
+        let pin = match input::parse(&candidate, reference) {
+            Ok(pin) if check::full(pin, reference, check::Policy::Ignore).is_ok() => pin,
+            _ => continue,
+        };
+
+        // count the lines up to the match
+        for (i, byte) in text[counted..start].iter().enumerate() {
+            if *byte == b'\n' {
+                line += 1;
+                line_start = counted + i + 1;
+            }
+        }
+        counted = start;
+
+        matches.push(Match {
+            start,
+            end,
+            line,
+            column: String::from_utf8_lossy(&text[line_start..start]).chars().count() + 1,
+            pin,
+        });
+    }
+
+    matches
+}
+
+
+
+
+/// How pins are masked when redacting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Replace every digit with X, XXXXXX-XXXX
+    Full,
+
+    /// Keep the birth date and mask the last four digits, 19850312-****
+    BirthDate,
+
+    /// Keep the last four digits and mask the birth date, ******-1234
+    LastFour,
+}
+
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Style, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "full" => Ok(Style::Full),
+            "birth-date" => Ok(Style::BirthDate),
+            "last-four" => Ok(Style::LastFour),
+            _ => Err(format!("{} is not full, birth-date or last-four", s)),
+        }
+    }
+}
+
+
+
+
+/// Mask every valid pin in a text
+///
+/// Only the digits of the pins found by [find] are replaced, separators and everything around
+/// the pins is kept byte for byte.
+///
+/// # Arguments
+/// * `text` The text to redact
+/// * `reference` The date pins are interpreted at, see [input::parse]
+/// * `style` How to mask the pins
+///
+/// # Returns
+/// The redacted text, always the same length as `text`
+pub fn redact(text: &[u8], reference: NaiveDate, style: Style) -> Vec<u8> {
+    let mut result = text.to_vec();
+
+    for found in find(text, reference) {
+        let digits = (found.start..found.end)
+            .filter(|i| text[*i].is_ascii_digit())
+            .collect::<Vec<usize>>();
+
+        // the last four digits are the serial number and the control digit
+        let serial = digits.len() - 4;
+
+        let (masked, mask) = match style {
+            Style::Full => (&digits[..], b'X'),
+            Style::BirthDate => (&digits[serial..], b'*'),
+            Style::LastFour => (&digits[..serial], b'*'),
+        };
+
+        for i in masked {
+            result[*i] = mask;
+        }
+    }
+
+    result
+}
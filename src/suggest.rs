@@ -0,0 +1,106 @@
+use chrono::NaiveDate;
+
+use crate::{ check, input };
+use crate::utils::Pin;
+
+
+
+/// The typo a suggestion assumes was made
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// Two neighbouring digits were swapped, positions counted from 1 among the 10 digits
+    Transposition { position: usize },
+
+    /// One digit was mistyped, position counted from 1 among the 10 digits
+    Substitution { position: usize, from: u8, to: u8 },
+}
+
+
+/// A valid pin the input might have been meant to be
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    /// The suggested pin written the same way as the input
+    pub text: String,
+    pub pin: Pin,
+    pub edit: Edit,
+}
+
+
+
+
+/// Rank a suggestion, lower is more likely
+///
+/// Swapped digits are the most common mistake when numbers are read out loud, followed by
+/// single digits that are close to the right one. Suggestions that change the kind of the pin
+/// are least likely.
+fn rank(suggestion: &Suggestion, original: &Pin) -> (bool, u8, u8, usize) {
+    let changes_kind = suggestion.pin.kind != original.kind;
+
+    match suggestion.edit {
+        Edit::Transposition { position } => (changes_kind, 0, 0, position),
+        Edit::Substitution { position, from, to } => (changes_kind, 1, from.abs_diff(to), position),
+    }
+}
+
+
+
+
+/// Find valid pins that differ from the input by one typo
+///
+/// Tries every single digit substitution and every swap of two neighbouring digits among the
+/// last 10 digits of the input. The centry and the separator are kept as they are.
+///
+/// # Arguments
+/// * `input` A pin that could be parsed but didn't pass [check::full]
+/// * `reference` The date pins are interpreted at, see [input::parse]
+///
+/// # Returns
+/// Valid pins ordered from most to least likely, empty if the input can't be parsed
+pub fn suggest(input: &str, reference: NaiveDate) -> Vec<Suggestion> {
+
+    let original = match input::parse(input, reference) {
+        Ok(pin) => pin,
+        Err(_) => return Vec::new(),
+    };
+
+    let chars = input.chars().collect::<Vec<char>>();
+
+    // positions of the 10 digits of the pin, skipping the centry and separator
+    let positions = (0..chars.len())
+        .filter(|i| chars[*i].is_ascii_digit())
+        .collect::<Vec<usize>>();
+    let positions = &positions[positions.len() - 10..];
+
+    let mut candidates = Vec::new();
+
+    for (i, position) in positions.iter().enumerate() {
+        let from = original.nums[i];
+
+        for to in 0..10 {
+            if to == from { continue; }
+
+            let mut changed = chars.clone();
+            changed[*position] = char::from(b'0' + to);
+            candidates.push((changed, Edit::Substitution { position: i + 1, from, to }));
+        }
+
+        if i + 1 < positions.len() && chars[*position] != chars[positions[i + 1]] {
+            let mut changed = chars.clone();
+            changed.swap(*position, positions[i + 1]);
+            candidates.push((changed, Edit::Transposition { position: i + 1 }));
+        }
+    }
+
+    let mut suggestions = candidates.into_iter()
+        .filter_map(|(changed, edit)| {
+            let text = changed.iter().collect::<String>();
+            let pin = input::parse(&text, reference).ok()?;
+
+            check::full(pin, reference, check::Policy::Error).ok()?;
+            Some(Suggestion { text, pin, edit })
+        })
+        .collect::<Vec<Suggestion>>();
+
+    suggestions.sort_by_key(|suggestion| rank(suggestion, &original));
+    suggestions
+}
@@ -0,0 +1,152 @@
+use chrono::NaiveDate;
+use std::error::Error;
+use std::io;
+
+use pin::Format;
+
+use crate::{ check_pin, normalize, wanted, Cli };
+use crate::report::summary;
+
+
+
+/// Columns added to the end of each row
+const COLUMNS: [&str; 4] = ["pin_valid", "pin_reason", "pin_normalized", "pin_birthdate"];
+
+
+
+
+/// Find the index of the column to check
+///
+/// # Arguments
+/// * `column` Name of the column or its position, counted from 1
+/// * `header` The header row, if there is one
+///
+/// # Returns
+/// The index of the column or Err if it can't be found
+fn find_column(column: &str, header: Option<&csv::StringRecord>) -> Result<usize, String> {
+
+    if let Some(index) = header.and_then(|header| header.iter().position(|name| name == column)) {
+        return Ok(index)
+    }
+
+    match column.parse::<usize>() {
+        Ok(0) => Err("Column positions start at 1".to_string()),
+        Ok(position) => Ok(position - 1),
+        Err(_) => Err(format!("Column {} not found", column)),
+    }
+}
+
+
+
+
+/// Check one column of a csv file and write all rows back with the results appended
+///
+/// Rows with valid or invalid numbers are left out if they are hidden by the arguments, and so
+/// are valid numbers that don't pass the filters. The totals are written to stderr so they
+/// don't end up in the csv. With --fail-fast no rows are read after the first invalid number.
+///
+/// # Arguments
+/// * `args` The command line arguments
+/// * `reference` The date numbers are interpreted at
+/// * `input` Where to read the csv from
+/// * `output` Where to write the csv with the results
+///
+/// # Returns
+/// If every number was valid, or Err if the csv couldn't be read or written, or the column
+/// couldn't be found
+pub fn run(
+    args: &Cli,
+    reference: NaiveDate,
+    input: impl io::Read,
+    output: impl io::Write,
+) -> Result<bool, Box<dyn Error>> {
+
+    if !args.delimiter.is_ascii() {
+        return Err("Delimiter must be a single ascii char".into())
+    }
+    let delimiter = args.delimiter as u8;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(args.header)
+        .flexible(true)
+        .from_reader(input);
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_writer(output);
+
+    let header = match args.header {
+        true => Some(reader.headers()?.clone()),
+        false => None,
+    };
+
+    let column = find_column(&args.column, header.as_ref())?;
+
+    // short rows are padded so the results always end up in the same columns
+    let width = header.as_ref().map_or(0, |header| header.len()).max(column + 1);
+
+    if let Some(mut header) = header {
+        header.extend(COLUMNS);
+        writer.write_record(&header)?;
+    }
+
+    let format = args.format.unwrap_or(Format::Long);
+    let mut valid_count = 0;
+    let mut invalid_count = 0;
+    let mut filtered_count = 0;
+
+    for row in reader.records() {
+        let mut row = row?;
+        let (pin, _) = normalize(row.get(column).unwrap_or("").trim(), args);
+
+        while row.len() < width {
+            row.push_field("");
+        }
+
+        match check_pin(pin, args, reference) {
+            Ok(parsed) if wanted(&parsed, args, reference) => {
+                valid_count += 1;
+                if !args.valid { continue; }
+
+                let birth_date = parsed.date()
+                    .and_then(|date| date.naive())
+                    .map(|date| date.to_string())
+                    .unwrap_or_default();
+
+                row.extend([
+                    "true".to_string(),
+                    String::new(),
+                    parsed.format(format, reference),
+                    birth_date,
+                ]);
+            },
+            Ok(_) => {
+                filtered_count += 1;
+                continue;
+            },
+            Err(reason) => {
+                invalid_count += 1;
+
+                if args.invalid {
+                    row.extend(["false", reason.code(), "", ""]);
+                    writer.write_record(&row)?;
+                }
+
+                if args.fail_fast { break; }
+                continue;
+            }
+        }
+
+        writer.write_record(&row)?;
+    }
+
+    writer.flush()?;
+
+    if args.count {
+        eprintln!("{}", summary(valid_count, invalid_count, filtered_count));
+    }
+
+    Ok(invalid_count == 0)
+}
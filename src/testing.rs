@@ -1,11 +1,21 @@
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
     use std::fs::File;
     use glob::glob;
     use std::io::{ BufReader, BufRead };
-    use crate::{ input, check };
-    
+    use chrono::NaiveDate;
+    use crate::{ input, check, denmark, explain, finland, generate, norway, pseudonym, scan, suggest };
+    use crate::utils::{ get_year, Country, Format, Gender, Group, Guess, Identity, Kind };
+    use crate::error::Error;
+
+
+    /// Fixed date to interpret pins at, so the results don't change as time passes
+    fn reference() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 12, 1).unwrap()
+    }
+
     /// Test all functions for validating a pin number using knowns outputs
     ///
     /// The input string is given to the parser and the result is compared to the given
@@ -21,20 +31,20 @@ mod tests {
     /// * `input` a example user input string to run the validity and parser checks on
     /// * `validity` if the output from [input::parse] should be Ok or Err
     /// * `parsed` expected output from [input::parse] if it is Ok. Ignored if `validity` is
-    /// set to false.
+    ///   set to false.
     /// * `output` expected output from [check::full]. Ignored if `validity` is set to false
     ///
     #[allow(dead_code)]
     fn full_test(input: &str, validity: bool, parsed: [u8;10], output: bool) {
 
-        let actual_parsed = input::parse(&input.to_string()); // result from function call
+        let actual_parsed = input::parse(input, reference()); // result from function call
 
         if !validity {
             // Since the expected parsing result is that the input is invalid
             // we will expect an error and panic otherwise
 
             // fail test if the function does not return an error
-            actual_parsed.expect_err(format!("{} parsed wich shouldn't be possible", input).as_str());
+            actual_parsed.expect_err(&format!("{} parsed wich shouldn't be possible", input));
             return;
         }
 
@@ -48,7 +58,9 @@ mod tests {
         // check the parsed input and compare result with `output`
         assert_eq!(
             check::full(
-                actual_parsed.unwrap()
+                actual_parsed.unwrap(),
+                reference(),
+                check::Policy::Error,
             ).is_ok(),
             output,
             "{} got unexpected check result", input);
@@ -64,20 +76,22 @@ mod tests {
 
         let path = "src/tests/skatteverket/*.txt";
 
-        for entry in glob(path).expect("Failed to get files") {
-            if let Ok(path) = entry {
-
-                let file = File::open(path.clone()).expect(format!("{} not found", path.display()).as_str());
-                let reader = BufReader::new(file);
-                
-                // parse file contents
-                for line in reader.lines() {
-                    match line {
-                        Err(_) => {}
-                        Ok(pin) => {
-                            let parsed = input::parse(&pin).expect(format!("{} failed with invalid format", pin).as_str());
-                            assert_eq!(check::full(parsed).is_ok(), true, "{} failed check", pin);
-                        }
+        // the files have pins up to the end of 2023, which would be born in the future at the
+        // usual reference date
+        let reference = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+
+        for path in glob(path).expect("Failed to get files").flatten() {
+
+            let file = File::open(path.clone()).unwrap_or_else(|_| panic!("{} not found", path.display()));
+            let reader = BufReader::new(file);
+
+            // parse file contents
+            for line in reader.lines() {
+                match line {
+                    Err(_) => {}
+                    Ok(pin) => {
+                        let parsed = input::parse(&pin, reference).unwrap_or_else(|_| panic!("{} failed with invalid format", pin));
+                        assert!(check::full(parsed, reference, check::Policy::Error).is_ok(), "{} failed check", pin);
                     }
                 }
             }
@@ -91,14 +105,14 @@ mod tests {
         //! the expected result provided by the file.
         //! 
         //! The file must be formated with each line being a single pin
-        //! The line always starts with "v " or "i " before any input
+        //! The line always starts with "Y " or "! " before any input
         //!
-        //! If the line starts with "v " it means the pin is valid (v for valid)
-        //! If the line starts with "i " it means the pin is invalid (i for invalid)
+        //! If the line starts with "Y " it means the pin is valid
+        //! If the line starts with "! " it means the pin is invalid
         //!
 
         let path = "src/tests/mixed.txt";
-        let file = File::open(path).expect(format!("{} not found", path).as_str());
+        let file = File::open(path).unwrap_or_else(|_| panic!("{} not found", path));
         let reader = BufReader::new(file);
         
         // parse file contents
@@ -106,18 +120,18 @@ mod tests {
             match line {
                 Err(_) => {}
                 Ok(pin) => {
-                    // split line by spaces backwards
-                    let split = pin.split_once("  ");
+                    // split the expected result from the pin
+                    let split = pin.split_once(' ');
 
                     if split.is_none() {
                         continue;
                     }
 
 
-                    let (pin, expected) = split.unwrap();
+                    let (expected, pin) = split.unwrap();
+
 
-                    
-                    match input::parse(&pin.to_string()){
+                    match input::parse(pin, reference()){
                         Err(reason) => {
                             if expected == "!" {
                                 println!("{} failed to parse - {}", pin, reason);
@@ -126,7 +140,7 @@ mod tests {
                             }
                         }
                         Ok(parsed) => {
-                            match check::full(parsed) {
+                            match check::full(parsed, reference(), check::Policy::Error) {
                                 Err(reason) => {
                                     if expected == "!" {
                                         println!("{} failed check - {}", pin, reason);
@@ -148,4 +162,603 @@ mod tests {
             }
         }
     }
+
+
+
+    #[test]
+    fn generated() {
+        //! generate pins over a range and make sure they are all accepted by the parser and the
+        //! checks when written in both the short and the long form
+
+        let from = NaiveDate::from_ymd_opt(1920, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2020, 12, 31).unwrap();
+
+        for pin in generate::range(from, to, 5000, reference()).expect("Range should be valid") {
+            for format in [Format::Short, Format::LongCompact] {
+                let text = pin.format(format, reference());
+                let parsed = input::parse(&text, reference()).unwrap_or_else(|_| panic!("{} failed with invalid format", text));
+
+                assert_eq!(parsed.nums, pin.nums, "{} parsed to other numbers", text);
+                assert_eq!(parsed.date.year, pin.date.year, "{} parsed to another year", text);
+                assert!(check::full(parsed, reference(), check::Policy::Error).is_ok(), "{} failed check", text);
+            }
+        }
+
+        // short ranges still get different serials and both genders
+        let year = generate::range(
+            NaiveDate::from_ymd_opt(1985, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(1985, 12, 31).unwrap(),
+            5,
+            reference(),
+        ).unwrap().collect::<Vec<_>>();
+
+        assert!(year.iter().any(|pin| pin.gender() == Gender::Female));
+        assert!(year.iter().any(|pin| pin.gender() == Gender::Male));
+        assert_eq!(year.iter().map(|pin| pin.nums[6..9].to_vec()).collect::<HashSet<_>>().len(), 5);
+
+        // each way a range can be wrong has its own error
+        assert_eq!(generate::range(to, from, 1, reference()).err(), Some(Error::ReversedRange));
+        assert_eq!(
+            generate::range(from, from, 1000, reference()).err(),
+            Some(Error::RangeTooSmall { count: 1000, available: 999 }),
+        );
+    }
+
+
+
+    #[test]
+    fn samordningsnummer() {
+        //! make sure samordningsnummer are classified and that the 60 added to the day is
+        //! removed from the birth date
+
+        let parsed = input::parse("197010632391", reference()).expect("Should parse");
+        assert_eq!(parsed.kind, Kind::Samordningsnummer);
+        assert_eq!(parsed.date.day, 3);
+        assert!(check::full(parsed, reference(), check::Policy::Error).is_ok());
+
+        let parsed = input::parse("201001012382", reference()).expect("Should parse");
+        assert_eq!(parsed.kind, Kind::Personnummer);
+        assert_eq!(parsed.date.day, 1);
+
+        // day 60 and days over 91 are not possible for either kind
+        for pin in ["197010602391", "197010922391", "197002902391"] {
+            let parsed = input::parse(pin, reference()).expect("Should parse");
+            assert!(check::full(parsed, reference(), check::Policy::Error).is_err(), "{} should not be valid", pin);
+        }
+    }
+
+
+
+    #[test]
+    fn organisationsnummer() {
+        //! check organisationsnummer in all accepted shapes and make sure the legal form group
+        //! is found
+
+        for org in ["5560360793", "556036-0793", "165560360793", "16556036-0793"] {
+            let parsed = input::parse_org(org).unwrap_or_else(|_| panic!("{} failed with invalid format", org));
+            assert_eq!(parsed.group, Some(Group::LimitedCompany));
+            assert!(check::org(parsed).is_ok(), "{} failed check", org);
+        }
+
+        // pins, wrong prefix, wrong separator, unknown group and wrong control digit
+        let cases = [
+            ("2010010123", Error::ThirdDigit { digit: 1 }),
+            ("195560360793", Error::InvalidPrefix),
+            ("556036+0793", Error::InvalidSeparator { position: 7, found: '+' }),
+            ("0160360793", Error::UnknownGroup { digit: 0 }),
+            ("5560360794", Error::Checksum { expected: 3, found: 4 }),
+        ];
+
+        for (org, expected) in cases {
+            let result = input::parse_org(org).and_then(check::org);
+            assert_eq!(result, Err(expected), "{} got the wrong error", org);
+        }
+    }
+
+
+
+    #[test]
+    fn errors() {
+        //! make sure each kind of invalid input gives the expected error
+
+        let cases = [
+            ("12345", Error::InvalidLength { length: 5, min: 10, max: 13 }),
+            ("850312*1234", Error::InvalidSeparator { position: 7, found: '*' }),
+            ("19850312*1234", Error::InvalidSeparator { position: 9, found: '*' }),
+            ("85031X-1234", Error::NotDigit { position: 6, found: 'X' }),
+            ("1985031212X4", Error::NotDigit { position: 11, found: 'X' }),
+            ("201013012382", Error::MonthOutOfRange { month: 13 }),
+            ("200002301234", Error::DayOutOfRange { day: 30, month: 2, year: 2000 }),
+            ("201001012383", Error::Checksum { expected: 2, found: 3 }),
+            ("20991231-1234", Error::FutureDate { day: 31, month: 12, year: 2099 }),
+        ];
+
+        for (pin, expected) in cases {
+            let result = input::parse(pin, reference()).and_then(|pin| check::full(pin, reference(), check::Policy::Error));
+            assert_eq!(result, Err(expected), "{} got the wrong error", pin);
+        }
+
+        // luhn and the modulus checks of other countries can be told apart
+        let modulus = Error::Modulus { modulus: 11, position: 10, expected: '8', found: '9' };
+        assert_eq!(Error::Checksum { expected: 2, found: 3 }.code(), "checksum");
+        assert_eq!(modulus.code(), "modulus");
+    }
+
+
+
+    #[test]
+    fn reference_date() {
+        //! the centry of 10 digit pins is guessed from the reference date, so the same pin can
+        //! be a different person depending on when it was read
+
+        let before = NaiveDate::from_ymd_opt(2024, 2, 28).unwrap();
+        let after = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+
+        assert_eq!(input::parse("240229-0000", before).unwrap().date.year, 1924);
+        assert_eq!(input::parse("240229-0000", after).unwrap().date.year, 2024);
+        assert_eq!(input::parse("240229+0000", after).unwrap().date.year, 1924);
+
+        // the full year is never guessed
+        assert_eq!(input::parse("192402290000", after).unwrap().date.year, 1924);
+
+        // the reason for the guess is kept so it can be explained
+        assert_eq!(get_year(24, 2, 29, before), (1924, Guess::Upcoming));
+        assert_eq!(get_year(24, 2, 29, after), (2024, Guess::Passed));
+        assert_eq!(get_year(25, 1, 1, after), (1925, Guess::After));
+        assert_eq!(get_year(23, 12, 31, after), (2023, Guess::Before));
+    }
+
+
+
+    #[test]
+    fn gender() {
+        //! the 9th digit is odd for men and even for women
+
+        assert_eq!(input::parse("201001012382", reference()).unwrap().gender(), Gender::Female);
+        assert_eq!(input::parse("201001012390", reference()).unwrap().gender(), Gender::Male);
+        assert_eq!("F".parse::<Gender>(), Ok(Gender::Female));
+        assert_eq!("male".parse::<Gender>(), Ok(Gender::Male));
+    }
+
+
+
+    #[test]
+    fn age() {
+        //! ages count whole years, with leap day birthdays happening on the 1st of march the
+        //! years without a 29th of february
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        let pin = input::parse("20080101-0018", reference()).unwrap();
+        assert_eq!(pin.age_at(date(2025, 12, 31)), 17);
+        assert_eq!(pin.age_at(date(2026, 1, 1)), 18);
+
+        let leap = generate::pin(date(2008, 2, 29), 1, reference()).unwrap();
+        assert_eq!(leap.age_at(date(2026, 2, 28)), 17);
+        assert_eq!(leap.age_at(date(2026, 3, 1)), 18);
+        assert_eq!(leap.age_at(date(2028, 2, 29)), 20);
+
+        // the extra 60 of samordningsnummer is not part of the birth date
+        let pin = input::parse("197010632391", reference()).unwrap();
+        assert_eq!(pin.age_at(date(2020, 10, 2)), 49);
+        assert_eq!(pin.age_at(date(2020, 10, 3)), 50);
+    }
+
+
+
+    #[test]
+    fn format() {
+        //! every accepted shape can be written in every format, with a + for people 100 years
+        //! or older at the reference date
+
+        let pin = input::parse("19250101-0017", reference()).unwrap();
+        assert_eq!(pin.format(Format::Long, reference()), "19250101-0017");
+        assert_eq!(pin.format(Format::LongCompact, reference()), "192501010017");
+        assert_eq!(pin.format(Format::Short, reference()), "250101-0017");
+        assert_eq!(pin.format(Format::ShortCompact, reference()), "2501010017");
+
+        let later = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        assert_eq!(pin.format(Format::Short, later), "250101+0017");
+        assert_eq!(pin.format(Format::Long, later), "19250101+0017");
+        assert_eq!(input::parse("19850312-1231", reference()).unwrap().to_string(), "19850312-1231");
+
+        // identities write the number like the type they hold, the kind has its own label
+        let org = input::parse_any("556036-0793", reference()).unwrap();
+        assert_eq!(org.to_string(), "556036-0793");
+        assert_eq!(org.kind_label(), "organisationsnummer limited-company");
+
+        assert_eq!("yymmdd-nnnn".parse::<Format>(), Ok(Format::Short));
+        assert!("YYMMDD".parse::<Format>().is_err());
+    }
+
+
+
+    #[test]
+    fn scanning() {
+        //! find pins of every shape in a text, but not invalid ones or ones that are part of
+        //! longer numbers
+
+        let text = "user 8501010014 logged in\n\
+                    ssn=850101-0014, other=19850101-0014; phone 0701234567\n\
+                    å 250101+0017 x198501010014y 12345678901234 850101-00141 8501010015";
+
+        let found = scan::find(text.as_bytes(), reference())
+            .iter()
+            .map(|found| (found.line, found.column, &text[found.start..found.end]))
+            .collect::<Vec<_>>();
+
+        assert_eq!(found, [
+            (1, 6, "8501010014"),
+            (2, 5, "850101-0014"),
+            (2, 24, "19850101-0014"),
+            (3, 3, "250101+0017"),
+            (3, 16, "198501010014"),
+        ]);
+    }
+
+
+
+    #[test]
+    fn redacting() {
+        //! only the digits of valid pins are masked and the text keeps its length
+
+        let text = "a 850101-0014, b 198501010014 c 850101-0015\r\n".as_bytes();
+
+        let cases = [
+            (scan::Style::Full, "a XXXXXX-XXXX, b XXXXXXXXXXXX c 850101-0015\r\n"),
+            (scan::Style::BirthDate, "a 850101-****, b 19850101**** c 850101-0015\r\n"),
+            (scan::Style::LastFour, "a ******-0014, b ********0014 c 850101-0015\r\n"),
+        ];
+
+        for (style, expected) in cases {
+            let redacted = scan::redact(text, reference(), style);
+            assert_eq!(String::from_utf8(redacted).unwrap(), expected);
+        }
+    }
+
+
+
+    #[test]
+    fn pseudonyms() {
+        //! fake pins are valid, keep the birth year and gender, and never collide
+
+        let from = NaiveDate::from_ymd_opt(1985, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(1985, 12, 31).unwrap();
+        let mut seen = std::collections::HashSet::new();
+
+        for pin in generate::range(from, to, 5000, reference()).unwrap() {
+            let fake = pseudonym::pseudonymize(pin, b"key", reference()).unwrap();
+
+            assert!(check::full(fake, reference(), check::Policy::Error).is_ok(), "{} is not valid", fake);
+            assert_eq!(fake.date.year, 1985);
+            assert_eq!(fake.gender(), pin.gender());
+            assert!(seen.insert(fake.nums), "{} was given twice", fake);
+
+            // the same key always gives the same pin, but another key doesn't
+            let again = pseudonym::pseudonymize(pin, b"key", reference()).unwrap();
+            let other = pseudonym::pseudonymize(pin, b"other key", reference()).unwrap();
+            assert_eq!(again.nums, fake.nums);
+            assert_ne!(other.nums, fake.nums);
+        }
+
+        // people born the reference year are not given birth dates after it
+        let from = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        for pin in generate::range(from, reference(), 1000, reference()).unwrap() {
+            let fake = pseudonym::pseudonymize(pin, b"key", reference()).unwrap();
+            assert!(fake.date.naive().unwrap() <= reference(), "{} is after the reference", fake);
+        }
+
+        let born = NaiveDate::from_ymd_opt(2023, 12, 2).unwrap();
+        let future = generate::pin(born, 1, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()).unwrap();
+        assert_eq!(
+            pseudonym::pseudonymize(future, b"key", reference()).err(),
+            Some(Error::FutureDate { day: 2, month: 12, year: 2023 }),
+        );
+    }
+
+
+
+    #[test]
+    fn suggestions() {
+        //! every suggestion is valid and one typo away, swapped digits come first
+
+        // 19850312-1231 with the serial digits 2 and 3 swapped
+        let found = suggest::suggest("19850312-1321", reference());
+        assert!(found.iter().any(|suggestion| suggestion.text == "19850312-1231"));
+        assert!(matches!(found[0].edit, suggest::Edit::Transposition { .. }));
+
+        for suggestion in &found {
+            assert!(check::full(suggestion.pin, reference(), check::Policy::Error).is_ok(), "{} is not valid", suggestion.text);
+            assert_eq!(suggestion.text.len(), "19850312-1321".len());
+        }
+
+        // a wrong control digit can always be fixed
+        let found = suggest::suggest("8503121230", reference());
+        assert!(found.iter().any(|suggestion| suggestion.edit == suggest::Edit::Substitution {
+            position: 10, from: 0, to: 1,
+        }));
+
+        // nothing to suggest for numbers that can't be parsed
+        assert!(suggest::suggest("85031", reference()).is_empty());
+    }
+
+
+
+    #[test]
+    fn check_digits() {
+        //! completing a pin gives the same pin as the one the digit was removed from
+
+        for pin in generate::range(NaiveDate::from_ymd_opt(1900, 1, 1).unwrap(), reference(), 200, reference()).unwrap() {
+            let text = pin.format(Format::Short, reference());
+            let completed = check::complete(&text[..text.len() - 1], reference()).unwrap();
+            assert_eq!(completed.nums, pin.nums);
+
+            let text = pin.format(Format::LongCompact, reference());
+            let completed = check::complete(&text[..text.len() - 1], reference()).unwrap();
+            assert_eq!(completed.nums, pin.nums);
+        }
+
+        assert_eq!(check::complete("850312-123", reference()).unwrap().nums[9], 1);
+        assert_eq!(check::complete("8503121", reference()).unwrap_err(), Error::InvalidLength { length: 7, min: 9, max: 12 });
+        assert_eq!(check::complete("851312-123", reference()).unwrap_err(), Error::MonthOutOfRange { month: 13 });
+    }
+
+
+
+    #[test]
+    fn explanations() {
+        //! every step is explained and the result matches the checks
+
+        let lines = explain::explain("850312-1236", reference());
+        assert_eq!(lines[0], "Shape: 11 chars, matches YYMMDD-NNNN");
+        assert!(lines.iter().any(|line| line.contains("85 is after 23 so it's the previous centry, year 1985")));
+        assert!(lines.iter().any(|line| line == "  product  16  5  0  3  2  2  2  2  6  6"));
+        assert!(lines.iter().any(|line| line.contains("total 35, not divisible by 10, control digit should be 1")));
+        assert_eq!(lines.last().unwrap(), "Result: invalid - Luhns, control digit is 6 but should be 1");
+
+        let lines = explain::explain("231202-1231", reference());
+        assert!(lines.iter().any(|line| line.contains("23 is this year but 12-02 hasn't happened yet so it's the previous centry, year 1923")));
+
+        let lines = explain::explain("19850312-1231", reference());
+        assert!(lines.contains(&"Centry: 19 is given, year 1985".to_string()));
+        assert_eq!(lines.last().unwrap(), "Result: valid personnummer");
+
+        // parsing stops early, so there is nothing more to explain
+        let lines = explain::explain("85031", reference());
+        assert_eq!(lines, ["Shape: 5 chars, pins are 10 to 13 chars", "Result: invalid - Too short, 5 chars"]);
+    }
+
+
+
+    #[test]
+    fn normalizing() {
+        //! noise is removed and reported, clean numbers are left as they are
+        use crate::input::Change;
+
+        let cases = [
+            ("850312-1231", "850312-1231", vec![]),
+            ("85 03 12-1231", "850312-1231", vec![Change::Whitespace]),
+            ("850312 1231\r", "8503121231", vec![Change::Whitespace]),
+            ("850312.1231", "8503121231", vec![Change::Dots]),
+            ("850312\u{2013}1231", "850312-1231", vec![Change::Dashes]),
+            ("\u{FF18}\u{FF15}0312\u{FF0B}1231", "850312+1231", vec![Change::FullWidth]),
+            (" 8503.12\u{2212}1231", "850312-1231", vec![Change::Whitespace, Change::Dots, Change::Dashes]),
+            ("\u{0668}\u{0665}0312-\u{0967}231", "850312-1231", vec![Change::Digits]),
+        ];
+
+        for (given, expected, changes) in cases {
+            let (normalized, found) = input::normalize(given);
+            assert_eq!(normalized, expected);
+            assert_eq!(found, changes, "{:?}", given);
+            assert!(input::parse(&normalized, reference()).is_ok());
+        }
+    }
+
+
+
+    #[test]
+    fn separators() {
+        //! the full year can be given with either separator, but it has to match the age
+
+        let cases = [
+            ("19120101+1234", Ok(())),
+            ("19120101-1234", Err(Error::PlusNeeded { age: 111 })),
+            ("19850312-1231", Ok(())),
+            ("19850312+1231", Err(Error::PlusNotAllowed { age: 38 })),
+
+            // turns 100 on the reference date
+            ("19231201+1234", Ok(())),
+            ("19231202+1234", Err(Error::PlusNotAllowed { age: 99 })),
+        ];
+
+        for (pin, expected) in cases {
+            let parsed = input::parse(pin, reference()).unwrap_or_else(|_| panic!("{} failed with invalid format", pin));
+            assert_eq!(parsed.plus, pin.contains('+'));
+            assert_eq!(check::separator(pin.chars().nth(8).unwrap(), parsed.date, reference()), expected, "{}", pin);
+        }
+
+        // the long format uses a + for people 100 or older, so it can be parsed back
+        let parsed = input::parse("19120101+1234", reference()).unwrap();
+        assert_eq!(parsed.format(Format::Long, reference()), "19120101+1234");
+
+        // validating checks the separator as well unless told not to, and displaying keeps it
+        let parsed = input::parse("19120101-1234", reference()).unwrap();
+        assert_eq!(parsed.validate(reference()), Err(Error::PlusNeeded { age: 111 }));
+        assert!(check::full(parsed, reference(), check::Policy::Ignore).is_ok());
+        assert_eq!(parsed.to_string(), "19120101-1234");
+
+        // without a separator the pin gets the one it should have
+        let parsed = input::parse("191201011234", reference()).unwrap();
+        assert!(parsed.validate(reference()).is_ok());
+        assert_eq!(parsed.to_string(), "19120101+1234");
+    }
+
+
+
+    #[test]
+    fn norwegian() {
+        //! fødselsnummer get their centry from the individual number, and d- and h-numbers have
+        //! the extra 40 removed from the birth date
+
+        let cases = [
+            ("31129949980", norway::Kind::Fodselsnummer, (31, 12, 1999)),
+            ("01010550048", norway::Kind::Fodselsnummer, (1, 1, 2005)),
+            ("01016060085", norway::Kind::Fodselsnummer, (1, 1, 1860)),
+            ("01016090073", norway::Kind::Fodselsnummer, (1, 1, 1960)),
+            ("41019012393", norway::Kind::DNummer, (1, 1, 1990)),
+            ("01419012382", norway::Kind::HNummer, (1, 1, 1990)),
+        ];
+
+        for (number, kind, (day, month, year)) in cases {
+            let parsed = norway::parse(number).unwrap_or_else(|_| panic!("{} failed with invalid format", number));
+            assert_eq!(parsed.kind, kind, "{}", number);
+            assert_eq!((parsed.date.day, parsed.date.month, parsed.date.year), (day, month, year), "{}", number);
+            assert!(norway::check(parsed, reference()).is_ok(), "{} failed check", number);
+        }
+
+        let cases = [
+            ("3112994998", Error::InvalidLength { length: 10, min: 11, max: 11 }),
+            ("01017580012", Error::IndividualNumber { individual: 800, year: 75 }),
+            ("31129949981", Error::Modulus { modulus: 11, position: 11, expected: '0', found: '1' }),
+            ("31029949980", Error::DayOutOfRange { day: 31, month: 2, year: 1999 }),
+            ("01013050000", Error::FutureDate { day: 1, month: 1, year: 2030 }),
+        ];
+
+        for (number, expected) in cases {
+            let result = norway::parse(number).and_then(|parsed| norway::check(parsed, reference()));
+            assert_eq!(result, Err(expected), "{} got the wrong error", number);
+        }
+
+        // 11 digits can only be norwegian, swedish numbers of that length have a separator
+        assert!(norway::detect("31129949980"));
+        assert!(!norway::detect("991231-4998"));
+    }
+
+
+
+    #[test]
+    fn danish() {
+        //! CPR-nummer get their centry from the 7th digit and only fail the modulus 11 check when
+        //! it is asked for
+
+        let cases = [
+            ("010190-1238", (1, 1, 1990), Gender::Female),
+            ("0101104125", (1, 1, 2010), Gender::Male),
+            ("010150-4123", (1, 1, 1950), Gender::Male),
+            ("010110-5121", (1, 1, 2010), Gender::Male),
+            ("010170-5129", (1, 1, 1870), Gender::Male),
+            ("010120-9121", (1, 1, 2020), Gender::Male),
+            ("010160-9006", (1, 1, 1960), Gender::Female),
+            ("290200-4125", (29, 2, 2000), Gender::Male),
+        ];
+
+        for (number, (day, month, year), gender) in cases {
+            let parsed = denmark::parse(number).unwrap_or_else(|_| panic!("{} failed with invalid format", number));
+            assert_eq!((parsed.date.day, parsed.date.month, parsed.date.year), (day, month, year), "{}", number);
+            assert_eq!(parsed.gender(), gender, "{}", number);
+            assert!(denmark::check(parsed, reference()).is_ok(), "{} failed check", number);
+            assert!(denmark::modulus(parsed).is_ok(), "{} failed modulus 11", number);
+        }
+
+        let cases = [
+            ("010190+1238", Error::InvalidSeparator { position: 7, found: '+' }),
+            ("010190-12", Error::InvalidLength { length: 9, min: 10, max: 11 }),
+            ("300290-1234", Error::DayOutOfRange { day: 30, month: 2, year: 1990 }),
+            ("010130-4000", Error::FutureDate { day: 1, month: 1, year: 2030 }),
+        ];
+
+        for (number, expected) in cases {
+            let result = denmark::parse(number).and_then(|parsed| denmark::check(parsed, reference()));
+            assert_eq!(result, Err(expected), "{} got the wrong error", number);
+        }
+
+        // newer numbers can fail the modulus 11 check and still be valid
+        let parsed = denmark::parse("010190-1239").unwrap();
+        assert!(denmark::check(parsed, reference()).is_ok());
+        assert_eq!(denmark::modulus(parsed), Err(Error::Modulus { modulus: 11, position: 10, expected: '8', found: '9' }));
+
+        assert_eq!(parsed.format(Format::ShortCompact), "0101901239");
+        assert_eq!(parsed.to_string(), "010190-1239");
+    }
+
+
+
+    #[test]
+    fn finnish() {
+        //! henkilötunnus get their centry from the sign after the birth date and end with a
+        //! control char from the remainder of dividing by 31
+
+        let cases = [
+            ("131052-308T", (13, 10, 1952), Gender::Female),
+            ("010594Y9021", (1, 5, 1994), Gender::Female),
+            ("010100A123D", (1, 1, 2000), Gender::Male),
+            ("311299+0029", (31, 12, 1899), Gender::Female),
+            ("290200A004E", (29, 2, 2000), Gender::Female),
+        ];
+
+        for (number, (day, month, year), gender) in cases {
+            let parsed = finland::parse(number).unwrap_or_else(|_| panic!("{} failed with invalid format", number));
+            assert_eq!((parsed.date.day, parsed.date.month, parsed.date.year), (day, month, year), "{}", number);
+            assert_eq!(parsed.gender(), gender, "{}", number);
+            assert!(finland::check(parsed, reference()).is_ok(), "{} failed check", number);
+            assert_eq!(parsed.to_string(), number);
+        }
+
+        let cases = [
+            ("131052-308", Error::InvalidLength { length: 10, min: 11, max: 11 }),
+            ("131052G308T", Error::InvalidSeparator { position: 7, found: 'G' }),
+            ("13105X-308T", Error::NotDigit { position: 6, found: 'X' }),
+            ("131052-308A", Error::Modulus { modulus: 31, position: 11, expected: 'T', found: 'A' }),
+            ("010190U001P", Error::IndividualNumber { individual: 1, year: 90 }),
+            ("300200A004E", Error::DayOutOfRange { day: 30, month: 2, year: 2000 }),
+            ("010130A0028", Error::FutureDate { day: 1, month: 1, year: 2030 }),
+        ];
+
+        for (number, expected) in cases {
+            let result = finland::parse(number).and_then(|parsed| finland::check(parsed, reference()));
+            assert_eq!(result, Err(expected), "{} got the wrong error", number);
+        }
+
+        // only letters tell a henkilötunnus apart from a swedish pin
+        assert!(finland::detect("010594Y9021"));
+        assert!(finland::detect("131052-308T"));
+        assert!(!finland::detect("311299+0029"));
+        assert!(!finland::detect("991231-4998"));
+    }
+
+
+
+    #[test]
+    fn countries() {
+        //! the country is detected from the shape of the number, except for danish numbers
+        //! which have to be asked for
+
+        let cases = [
+            ("31129949980", Country::No),
+            ("010594Y9021", Country::Fi),
+            ("131052-308T", Country::Fi),
+            ("850312-1231", Country::Se),
+            ("556036-0793", Country::Se),
+            ("010190-1238", Country::Se),
+        ];
+
+        for (number, country) in cases {
+            assert_eq!(Country::detect(number), country, "{}", number);
+        }
+
+        for (number, country) in cases.iter().take(5) {
+            let parsed = input::parse_any(number, reference()).unwrap_or_else(|_| panic!("{} failed with invalid format", number));
+            assert_eq!(parsed.country(), *country, "{}", number);
+            assert!(parsed.validate(reference()).is_ok(), "{} failed check", number);
+        }
+
+        let parsed: Identity = "31129949980".parse().unwrap();
+        assert!(matches!(parsed, Identity::Norwegian(_)));
+        assert_eq!(parsed.country().to_string(), "no");
+
+        let parsed = input::parse_country("010190-1238", Country::Dk, reference()).unwrap();
+        assert!(matches!(parsed, Identity::Danish(_)));
+        assert!(matches!(input::parse_country("556036-0793", Country::Se, reference()), Ok(Identity::Organisation(_))));
+        assert!(input::parse_country("8503121231", Country::Fi, reference()).is_err());
+    }
 }
@@ -1,4 +1,13 @@
 use chrono::prelude::*;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{ check, finland, input, norway };
+use crate::denmark::Cpr;
+use crate::finland::Hetu;
+use crate::norway::Fodselsnummer;
+use crate::error::Error;
 
 
 /// Represents the date a pin is for
@@ -10,12 +19,537 @@ pub struct Date {
 }
 
 
+impl Date {
+    /// Convert to a chrono date
+    ///
+    /// # Returns
+    /// The date or None if it doesn't exist, i.e the pin hasn't passed [check::full]
+    pub fn naive(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year, self.month, self.day)
+    }
+
+    /// Calculate how old someone born on this date is at the given date
+    ///
+    /// Someone born on the 29th of february turns a year older on the 1st of march the years
+    /// that aren't leap years.
+    ///
+    /// # Arguments
+    /// * `at` The date to calculate the age at
+    ///
+    /// # Returns
+    /// The age in whole years, negative if `at` is before the birth date
+    pub fn age_at(&self, at: NaiveDate) -> i32 {
+        let mut age = at.year() - self.year;
+
+        // birthday hasn't happened yet that year
+        if (at.month(), at.day()) < (self.month, self.day) {
+            age -= 1;
+        }
+
+        age
+    }
+}
+
+
+/// The kind of number a pin is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Regular personal identity number
+    Personnummer,
+
+    /// Coordination number, given to people who aren't registered in sweden. The day of the
+    /// number is increased by 60
+    Samordningsnummer,
+}
+
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Kind::Personnummer => "personnummer",
+            Kind::Samordningsnummer => "samordningsnummer",
+        })
+    }
+}
+
+
+/// Legal gender of the person a pin belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gender {
+    Female,
+    Male,
+}
+
+
+impl fmt::Display for Gender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Gender::Female => "female",
+            Gender::Male => "male",
+        })
+    }
+}
+
+
+impl FromStr for Gender {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Gender, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "female" | "f" => Ok(Gender::Female),
+            "male" | "m" => Ok(Gender::Male),
+            _ => Err(format!("{} is not female or male", s)),
+        }
+    }
+}
+
+
+/// Why [get_year] picked the centry it did for a year written with two digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guess {
+    /// The two digits are after the year of the reference date, so it's the previous centry
+    After,
+
+    /// The two digits are before the year of the reference date, so it's the same centry
+    Before,
+
+    /// Same two digits as the reference date but the birthday hasn't happened yet that year,
+    /// so it's the previous centry
+    Upcoming,
+
+    /// Same two digits as the reference date and the birthday has happened, so it's the same
+    /// centry
+    Passed,
+}
+
+
+/// The ways a number can be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// YYYYMMDD-NNNN, with a + instead of the - for people 100 years or older
+    Long,
+
+    /// YYYYMMDDNNNN
+    LongCompact,
+
+    /// YYMMDD-NNNN, with a + instead of the - for people 100 years or older
+    Short,
+
+    /// YYMMDDNNNN, can't show if the person is 100 years or older
+    ShortCompact,
+}
+
+
+impl Format {
+    /// Find the format a number was written in from its length
+    ///
+    /// # Arguments
+    /// * `input` The number as it was written
+    ///
+    /// # Returns
+    /// The format or None if the length doesn't match any format
+    pub fn detect(input: &str) -> Option<Format> {
+        match input.chars().count() {
+            10 => Some(Format::ShortCompact),
+            11 => Some(Format::Short),
+            12 => Some(Format::LongCompact),
+            13 => Some(Format::Long),
+            _ => None,
+        }
+    }
+}
+
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "YYYYMMDD-NNNN" => Ok(Format::Long),
+            "YYYYMMDDNNNN" => Ok(Format::LongCompact),
+            "YYMMDD-NNNN" => Ok(Format::Short),
+            "YYMMDDNNNN" => Ok(Format::ShortCompact),
+            _ => Err(format!(
+                "{} is not YYYYMMDD-NNNN, YYYYMMDDNNNN, YYMMDD-NNNN or YYMMDDNNNN", s
+            )),
+        }
+    }
+}
+
+
 /// Represents a fully parsed pin
 #[derive(Debug, Clone, Copy)]
 pub struct Pin {
     pub nums: [u8;10],
     pub plus: bool,
     pub date: Date,
+    pub kind: Kind,
+}
+
+
+impl Pin {
+    /// Run all checks on the pin at the given date, see [check::full]
+    pub fn validate(&self, reference: NaiveDate) -> Result<(), Error> {
+        check::full(*self, reference, check::Policy::Error)
+    }
+
+    /// Calculate the age of the person at the given date, see [Date::age_at]
+    pub fn age_at(&self, at: NaiveDate) -> i32 {
+        self.date.age_at(at)
+    }
+
+    /// Get the legal gender from the 9th digit, odd for male and even for female
+    pub fn gender(&self) -> Gender {
+        match self.nums[8] % 2 {
+            0 => Gender::Female,
+            _ => Gender::Male,
+        }
+    }
+
+    /// Get the separator the pin is written with, + if it was given with one or if it is needed
+    /// at the date it was parsed at
+    pub fn separator(&self) -> char {
+        match self.plus {
+            true => '+',
+            false => '-',
+        }
+    }
+
+    /// Write the pin in the given format
+    ///
+    /// Samordningsnummer keep the 60 added to the day.
+    ///
+    /// # Arguments
+    /// * `format` How to write the pin
+    /// * `reference` The date used to decide if a + is needed as separator
+    ///
+    /// # Returns
+    /// The formatted pin, for example `19850312-1234`
+    pub fn format(&self, format: Format, reference: NaiveDate) -> String {
+        let separator = match self.age_at(reference) >= 100 {
+            true => '+',
+            false => '-',
+        };
+
+        self.write(format, separator)
+    }
+
+    /// Write the pin in the given format with the given separator, see [Pin::format]
+    fn write(&self, format: Format, separator: char) -> String {
+        let digits = self.nums.iter().map(|n| n.to_string()).collect::<String>();
+        let centry = self.date.year.div_euclid(100);
+
+        match format {
+            Format::Long => format!("{:02}{}{}{}", centry, &digits[..6], separator, &digits[6..]),
+            Format::LongCompact => format!("{:02}{}", centry, digits),
+            Format::Short => format!("{}{}{}", &digits[..6], separator, &digits[6..]),
+            Format::ShortCompact => digits,
+        }
+    }
+}
+
+
+impl fmt::Display for Pin {
+    /// Writes the pin as YYYYMMDD-NNNN with the separator it has, so it doesn't depend on the
+    /// current date. Use [Pin::format] to get the separator for a reference date.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.write(Format::Long, self.separator()))
+    }
+}
+
+
+impl FromStr for Pin {
+    type Err = Error;
+
+    /// Parse a pin with [today] as reference, see [input::parse]
+    fn from_str(s: &str) -> Result<Pin, Self::Err> {
+        input::parse(s, today())
+    }
+}
+
+
+impl TryFrom<&str> for Pin {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Pin, Self::Error> {
+        value.parse()
+    }
+}
+
+
+/// The legal form group of a organisationsnummer, given by its first digit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    /// 1, dödsbon
+    Estate,
+
+    /// 2, the state, regions, municipalities and parishes
+    PublicSector,
+
+    /// 3, foreign companies with business or property in sweden
+    ForeignCompany,
+
+    /// 5, aktiebolag
+    LimitedCompany,
+
+    /// 6, enkla bolag
+    SimplePartnership,
+
+    /// 7, ekonomiska föreningar and bostadsrättsföreningar
+    EconomicAssociation,
+
+    /// 8, ideella föreningar and stiftelser
+    NonProfit,
+
+    /// 9, handelsbolag and kommanditbolag
+    TradingPartnership,
+}
+
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Group::Estate => "estate",
+            Group::PublicSector => "public-sector",
+            Group::ForeignCompany => "foreign-company",
+            Group::LimitedCompany => "limited-company",
+            Group::SimplePartnership => "simple-partnership",
+            Group::EconomicAssociation => "economic-association",
+            Group::NonProfit => "non-profit",
+            Group::TradingPartnership => "trading-partnership",
+        })
+    }
+}
+
+
+/// Represents a fully parsed organisationsnummer
+#[derive(Debug, Clone, Copy)]
+pub struct Org {
+    pub nums: [u8;10],
+    pub group: Option<Group>,
+}
+
+
+impl Org {
+    /// Run all checks on the organisationsnummer, see [check::org]
+    pub fn validate(&self) -> Result<(), Error> {
+        check::org(*self)
+    }
+
+    /// Write the organisationsnummer in the given format
+    ///
+    /// The long formats use 16 in place of the centry of a pin.
+    ///
+    /// # Arguments
+    /// * `format` How to write the number
+    ///
+    /// # Returns
+    /// The formatted number, for example `556036-0793`
+    pub fn format(&self, format: Format) -> String {
+        let digits = self.nums.iter().map(|n| n.to_string()).collect::<String>();
+
+        match format {
+            Format::Long => format!("16{}-{}", &digits[..6], &digits[6..]),
+            Format::LongCompact => format!("16{}", digits),
+            Format::Short => format!("{}-{}", &digits[..6], &digits[6..]),
+            Format::ShortCompact => digits,
+        }
+    }
+}
+
+
+impl fmt::Display for Org {
+    /// Writes the organisationsnummer as NNNNNN-NNNN
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.format(Format::Short))
+    }
+}
+
+
+impl FromStr for Org {
+    type Err = Error;
+
+    /// Parse a organisationsnummer, see [input::parse_org]
+    fn from_str(s: &str) -> Result<Org, Self::Err> {
+        input::parse_org(s)
+    }
+}
+
+
+impl TryFrom<&str> for Org {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Org, Self::Error> {
+        value.parse()
+    }
+}
+
+
+/// The country a number is from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Country {
+    /// Sweden, personnummer, samordningsnummer and organisationsnummer
+    Se,
+
+    /// Norway, fødselsnummer, d-nummer and h-nummer
+    No,
+
+    /// Denmark, CPR-nummer
+    Dk,
+
+    /// Finland, henkilötunnus
+    Fi,
+}
+
+
+impl Country {
+    /// Guess the country of a number from its shape
+    ///
+    /// Norwegian numbers are 11 digits with no separator and finnish ones have letters where
+    /// swedish pins never do, see [crate::norway::detect] and [crate::finland::detect]. Danish
+    /// numbers look just like swedish pins so they are never detected.
+    pub fn detect(input: &str) -> Country {
+        if norway::detect(input) {
+            return Country::No
+        }
+
+        match finland::detect(input) {
+            true => Country::Fi,
+            false => Country::Se,
+        }
+    }
+}
+
+
+impl fmt::Display for Country {
+    /// Writes the two letter code of the country
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Country::Se => "se",
+            Country::No => "no",
+            Country::Dk => "dk",
+            Country::Fi => "fi",
+        })
+    }
+}
+
+
+/// Any of the kinds of numbers that can be checked
+#[derive(Debug, Clone, Copy)]
+pub enum Identity {
+    Person(Pin),
+    Organisation(Org),
+    Norwegian(Fodselsnummer),
+    Danish(Cpr),
+    Finnish(Hetu),
+}
+
+
+impl Identity {
+    /// Run all checks for the kind of number, pins are checked at the given date
+    pub fn validate(&self, reference: NaiveDate) -> Result<(), Error> {
+        match self {
+            Identity::Person(pin) => pin.validate(reference),
+            Identity::Organisation(org) => org.validate(),
+            Identity::Norwegian(number) => number.validate(reference),
+            Identity::Danish(number) => number.validate(reference),
+            Identity::Finnish(number) => number.validate(reference),
+        }
+    }
+
+    /// Get the country the number is from
+    pub fn country(&self) -> Country {
+        match self {
+            Identity::Person(_) | Identity::Organisation(_) => Country::Se,
+            Identity::Norwegian(_) => Country::No,
+            Identity::Danish(_) => Country::Dk,
+            Identity::Finnish(_) => Country::Fi,
+        }
+    }
+
+    /// Write the number in the given format, see [Pin::format], [Org::format] and [Cpr::format]
+    ///
+    /// Norwegian and finnish numbers only have one format and are always written the same way.
+    pub fn format(&self, format: Format, reference: NaiveDate) -> String {
+        match self {
+            Identity::Person(pin) => pin.format(format, reference),
+            Identity::Organisation(org) => org.format(format),
+            Identity::Norwegian(number) => number.to_string(),
+            Identity::Danish(number) => number.format(format),
+            Identity::Finnish(number) => number.to_string(),
+        }
+    }
+
+    /// Get the birth date, organisationsnummer don't have one
+    pub fn date(&self) -> Option<Date> {
+        match self {
+            Identity::Person(pin) => Some(pin.date),
+            Identity::Organisation(_) => None,
+            Identity::Norwegian(number) => Some(number.date),
+            Identity::Danish(number) => Some(number.date),
+            Identity::Finnish(number) => Some(number.date),
+        }
+    }
+
+    /// Get a label for the kind of number, like `personnummer` or `organisationsnummer
+    /// limited-company`
+    pub fn kind_label(&self) -> String {
+        match self {
+            Identity::Person(pin) => pin.kind.to_string(),
+            Identity::Organisation(org) => match org.group {
+                Some(group) => format!("organisationsnummer {}", group),
+                None => "organisationsnummer".to_string(),
+            },
+            Identity::Norwegian(number) => number.kind.to_string(),
+            Identity::Danish(_) => "cpr-nummer".to_string(),
+            Identity::Finnish(_) => "henkilötunnus".to_string(),
+        }
+    }
+
+    /// Get the legal gender, organisationsnummer don't have one
+    pub fn gender(&self) -> Option<Gender> {
+        match self {
+            Identity::Person(pin) => Some(pin.gender()),
+            Identity::Organisation(_) => None,
+            Identity::Norwegian(number) => Some(number.gender()),
+            Identity::Danish(number) => Some(number.gender()),
+            Identity::Finnish(number) => Some(number.gender()),
+        }
+    }
+}
+
+
+impl FromStr for Identity {
+    type Err = Error;
+
+    /// Parse a number of any country that can be detected with [today] as reference, see
+    /// [input::parse_any]
+    fn from_str(s: &str) -> Result<Identity, Self::Err> {
+        input::parse_any(s, today())
+    }
+}
+
+
+impl TryFrom<&str> for Identity {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Identity, Self::Error> {
+        value.parse()
+    }
+}
+
+
+impl fmt::Display for Identity {
+    /// Writes the number the same way as the type it holds
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Identity::Person(pin) => pin.fmt(f),
+            Identity::Organisation(org) => org.fmt(f),
+            Identity::Norwegian(number) => number.fmt(f),
+            Identity::Danish(number) => number.fmt(f),
+            Identity::Finnish(number) => number.fmt(f),
+        }
+    }
 }
 
 
@@ -69,46 +603,98 @@ pub fn max_day(month:u32, year:i32) -> u32 {
 }
 
 
-/// Use the current year to calculate a resonable guess for the year the pin is refrencing.
+/// The date to use as reference when none is given, today in UTC
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+
+/// Use the reference date to calculate a resonable guess for the year the pin is refrencing.
 ///
-/// If decade year in the pin is more than the current decade. The centry must be 100 less than
-/// what it is now. i.e if the current year is 2023 and `syear` is 24, the pin year must be 1924.
+/// If decade year in the pin is more than the reference decade. The centry must be 100 less than
+/// the reference. i.e if the reference year is 2023 and `syear` is 24, the pin year must be 1924.
 ///
 /// # Arguments
 /// * `syear` The decade given by the first 2 numbers of the pin
 /// * `month` The month given by the 3rd and 4th numbers of the pin
 /// * `day` The day given by the 5th and 6th numbers of the pin
+/// * `reference` The date the pin is interpreted at, usually [today]
 ///
 /// # Returns
-/// A full year, for example `2023`
-fn get_year(syear: i32, month:u32, day:u32) -> i32 {
+/// A full year, for example `2023`, and why that centry was picked
+pub fn get_year(syear: i32, month:u32, day:u32, reference: NaiveDate) -> (i32, Guess) {
 
-    // get current time
-    let time = Utc::now();
+    let time = reference;
 
-    let mut centry: i32 = time.year() / 100; // OOxx
+    let centry: i32 = time.year() / 100; // OOxx
     let decade: i32 = time.year() % 100; // xxOO
 
+    // if the pins year has a decade higher than the current decade, it must be from the
+    // previous centry, and so must a birthday later this year
+    let guess = match syear.cmp(&decade) {
+        Ordering::Greater => Guess::After,
+        Ordering::Less => Guess::Before,
+        Ordering::Equal if (month, day) > (time.month(), time.day()) => Guess::Upcoming,
+        Ordering::Equal => Guess::Passed,
+    };
 
-    if syear > decade { // if the pins year has a decade higher than the current decade, it must be
-                        // from the previous centry
-        centry -= 1;
-    }
+    let centry = match guess {
+        Guess::After | Guess::Upcoming => centry - 1,
+        Guess::Before | Guess::Passed => centry,
+    };
 
-    if syear == decade {
-        if month > time.month() {
-            centry -= 1;
-        }
-        if month == time.month() {
-            if day > time.day() {
-                centry -= 1;
-            }
-        }
+    (syear + centry*100, guess)
+}
+
+
+
+
+
+
+
+/// Get what kind of number a pin is
+///
+/// Samordningsnummer have 60 added to the day, so any day over 60 means the pin is one.
+///
+/// # Arguments
+/// * `nums` Array representing a pin
+///
+/// # Returns
+/// The kind of the pin
+pub fn get_kind(nums: [u8;10]) -> Kind {
+    let day = nums[4]*10 + nums[5]; // xxxxOO-xxxx
+
+    match day > 60 {
+        true => Kind::Samordningsnummer,
+        false => Kind::Personnummer,
     }
+}
 
-    let year = syear + centry*100;
 
-    return year;
+
+
+
+
+
+/// Get the legal form group of a organisationsnummer
+///
+/// # Arguments
+/// * `nums` Array representing a organisationsnummer
+///
+/// # Returns
+/// The group or None if the first digit isn't used for any group
+pub fn get_group(nums: [u8;10]) -> Option<Group> {
+    match nums[0] {
+        1 => Some(Group::Estate),
+        2 => Some(Group::PublicSector),
+        3 => Some(Group::ForeignCompany),
+        5 => Some(Group::LimitedCompany),
+        6 => Some(Group::SimplePartnership),
+        7 => Some(Group::EconomicAssociation),
+        8 => Some(Group::NonProfit),
+        9 => Some(Group::TradingPartnership),
+        _ => None,
+    }
 }
 
 
@@ -122,26 +708,34 @@ fn get_year(syear: i32, month:u32, day:u32) -> i32 {
 /// If a centry is given the plus flag is ignored as it would make no sense to, for example, take
 /// 2006 - 100 = 1906 if the plus flag was set with the centry being given as 2000
 ///
+/// For samordningsnummer the 60 added to the day is removed, so the date is the actual birth
+/// date.
+///
 /// # Arguments
 /// * `nums` Array representing a pin
 /// * `plus` Flag indicating if the year should be reduced by 100
 /// * `centry` Optional value if the years centry is also known
+/// * `reference` The date to guess the centry from if it isn't known
 ///
 /// # Returns
 /// Date object
-pub fn get_date(nums: [u8;10], plus: bool, centry: Option<i32>) -> Date {
+pub fn get_date(nums: [u8;10], plus: bool, centry: Option<i32>, reference: NaiveDate) -> Date {
 
     // Get date info from pin numbers
     let decade  = (nums[0]*10 + nums[1]) as i32; // OOxxxx-xxxx
     let month   = (nums[2]*10 + nums[3]) as u32; // xxOOxx-xxxx
-    let day     = (nums[4]*10 + nums[5]) as u32; // xxxxOO-xxxx
+    let mut day = (nums[4]*10 + nums[5]) as u32; // xxxxOO-xxxx
+
+    if get_kind(nums) == Kind::Samordningsnummer {
+        day -= 60;
+    }
 
 
     // if a known centry was supplied, use it
     // otherwise, calculate resonable guess based on nums and plus flag
     let year = match centry {
         Some(centry) => centry*100 + decade,
-        None => get_year(decade, month, day) - 100*(plus as i32)
+        None => get_year(decade, month, day, reference).0 - 100*(plus as i32)
     };
 
     // return date struct